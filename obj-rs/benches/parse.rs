@@ -0,0 +1,116 @@
+//! Parses the fixtures repeated many times, and a generated file of a million vertices, to measure
+//! the throughput of the parsers.
+//!
+//! Run with `cargo bench -p obj-rs`.
+//!
+//! `parse_obj` and `parse_mtl` read their input in blocks and parse them with the same lexer as
+//! the `_bytes` variants. To compare with an earlier version, run the benchmarks on a checkout of
+//! it with `-- --save-baseline <name>`, and then here with `-- --baseline <name>`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use obj::raw::{parse_mtl, parse_mtl_bytes, parse_obj, parse_obj_bytes, parse_obj_events};
+use std::fs;
+use std::hint::black_box;
+
+/// Number of times each fixture is repeated. Indices of a repeated `.obj` file still refer to
+/// vertices of the first copy, so every copy is valid.
+const SCALE: usize = 1000;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let mut input = fs::read(format!("tests/fixtures/{name}")).unwrap();
+    input.push(b'\n');
+    input.repeat(SCALE)
+}
+
+fn obj(c: &mut Criterion) {
+    let mut group = c.benchmark_group("obj");
+    for name in ["dome.obj", "normal-cone.obj", "textured-cube.obj"] {
+        let input = fixture(name);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("parse_obj", name), &input, |b, input| {
+            b.iter(|| parse_obj(black_box(&input[..])).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("parse_obj_bytes", name),
+            &input,
+            |b, input| b.iter(|| parse_obj_bytes(black_box(input)).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parse_obj_events", name),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    parse_obj_events(black_box(&input[..]))
+                        .map(Result::unwrap)
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn mtl(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mtl");
+    let input = fixture("map_options.mtl");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("parse_mtl", |b| {
+        b.iter(|| parse_mtl(black_box(&input[..])).unwrap())
+    });
+    group.bench_function("parse_mtl_bytes", |b| {
+        b.iter(|| parse_mtl_bytes(black_box(&input[..])).unwrap())
+    });
+    group.finish();
+}
+
+/// A million vertices with positions, texture coordinates and normals, with the given number of
+/// digits after the decimal point.
+fn generate_vertices(precision: usize) -> String {
+    let mut input = String::new();
+    for i in 0..1_000_000 {
+        let x = i as f32 * 0.001;
+        input += &format!(
+            "v {x:.precision$} {:.precision$} {:.precision$}\n",
+            -x,
+            x * 0.5
+        );
+        input += &format!(
+            "vt {:.precision$} {:.precision$}\n",
+            x.fract(),
+            1.0 - x.fract()
+        );
+        input += &format!(
+            "vn {:.precision$} {:.precision$} {:.precision$}\n",
+            x.sin(),
+            x.cos(),
+            0.0
+        );
+    }
+    input
+}
+
+/// A million vertices written the way exporters usually write them, which take the fast path of
+/// float parsing. The same vertices with 10 digits after the decimal point are too long for it, so
+/// they measure the fallback to `str::parse::<f32>`.
+fn vertices(c: &mut Criterion) {
+    let input = generate_vertices(6);
+    let long = generate_vertices(10);
+
+    let mut group = c.benchmark_group("vertices");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(3_000_000));
+    group.bench_function("parse_obj_bytes", |b| {
+        b.iter(|| parse_obj_bytes(black_box(input.as_bytes())).unwrap())
+    });
+    group.bench_function("parse_obj_bytes/str_parse", |b| {
+        b.iter(|| parse_obj_bytes(black_box(long.as_bytes())).unwrap())
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("parse_obj_parallel", |b| {
+        b.iter(|| obj::raw::parse_obj_parallel(black_box(input.as_bytes())).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, obj, mtl, vertices);
+criterion_main!(benches);
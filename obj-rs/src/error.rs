@@ -18,8 +18,16 @@ pub enum ObjError {
     /// IO error has been occurred during opening the `obj` file.
     Io(io::Error),
     /// Tried to parse integer frome the `obj` file, but failed.
+    ///
+    /// The parsers no longer return this variant. Numbers which fail to parse are reported as
+    /// `LoadErrorKind::WrongTypeOfArguments`, along with the line, statement and token. It is
+    /// kept for backwards compatibility.
     ParseInt(ParseIntError),
     /// Tried to parse floating point number frome the `obj` file, but failed.
+    ///
+    /// The parsers no longer return this variant. Numbers which fail to parse are reported as
+    /// `LoadErrorKind::WrongTypeOfArguments`, along with the line, statement and token. It is
+    /// kept for backwards compatibility.
     ParseFloat(ParseFloatError),
     /// `LoadError` has been occurred during parseing the `obj` file.
     Load(LoadError),
@@ -62,11 +70,32 @@ implmnt!(ParseInt, ParseIntError);
 implmnt!(ParseFloat, ParseFloatError);
 implmnt!(Load, LoadError);
 
+impl ObjError {
+    /// Attaches the location of the statement being parsed to the error, converting numeric
+    /// parse errors into `LoadError`s on the way.
+    pub(crate) fn at(self, line: usize, statement: &str) -> Self {
+        let error = match self {
+            ObjError::Load(e) => e,
+            ObjError::ParseInt(e) => {
+                LoadError::new_internal(LoadErrorKind::WrongTypeOfArguments, e.to_string())
+            }
+            ObjError::ParseFloat(e) => {
+                LoadError::new_internal(LoadErrorKind::WrongTypeOfArguments, e.to_string())
+            }
+            e => return e,
+        };
+        ObjError::Load(error.at(line, statement))
+    }
+}
+
 /// The error type for parse operations of the `Obj` struct.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct LoadError {
     kind: LoadErrorKind,
     message: String,
+    line: Option<usize>,
+    statement: Option<String>,
+    token: Option<String>,
 }
 
 impl LoadError {
@@ -74,6 +103,24 @@ impl LoadError {
     pub fn kind(&self) -> &LoadErrorKind {
         &self.kind
     }
+
+    /// The 1-based line number where the failed statement starts, if the error occurred while
+    /// parsing a statement.
+    ///
+    /// Lines connected with backslashes (`\`) are reported as their first line.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Keyword of the failed statement, if the error occurred while parsing a statement.
+    pub fn statement(&self) -> Option<&str> {
+        self.statement.as_deref()
+    }
+
+    /// The offending argument, if the error was caused by a specific one.
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
 }
 
 /// Enum to store the various types of errors that can cause loading an OBJ to fail.
@@ -81,6 +128,8 @@ impl LoadError {
 pub enum LoadErrorKind {
     /// Met unexpected statement.
     UnexpectedStatement,
+    /// Met a statement which is not supported yet.
+    UnsupportedStatement,
     /// Received wrong number of arguments.
     WrongNumberOfArguments,
     /// Received unexpected type of arguments.
@@ -104,12 +153,31 @@ impl LoadError {
         note = "You shouldn’t need to create a LoadError instance on your own."
     )]
     pub fn new(kind: LoadErrorKind, message: &'static str) -> Self {
-        let message = message.to_string();
-        LoadError { kind, message }
+        LoadError::new_internal(kind, message.to_string())
     }
 
     pub(crate) fn new_internal(kind: LoadErrorKind, message: String) -> Self {
-        LoadError { kind, message }
+        LoadError {
+            kind,
+            message,
+            line: None,
+            statement: None,
+            token: None,
+        }
+    }
+
+    pub(crate) fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Fills the location of the error, unless it is already known.
+    pub(crate) fn at(mut self, line: usize, statement: &str) -> Self {
+        self.line.get_or_insert(line);
+        if self.statement.is_none() && !statement.is_empty() {
+            self.statement = Some(statement.to_string());
+        }
+        self
     }
 }
 
@@ -121,6 +189,7 @@ impl fmt::Display for LoadError {
 
         let msg = match self.kind {
             UnexpectedStatement => "Met unexpected statement",
+            UnsupportedStatement => "Met unsupported statement",
             WrongNumberOfArguments => "Received wrong number of arguments",
             WrongTypeOfArguments => "Received unexpected type of arguments",
             UntriangulatedModel => "Model should be triangulated first to be loaded properly",
@@ -130,7 +199,22 @@ impl fmt::Display for LoadError {
             TooBigGroupNumber => "Group number exceeded limitation.",
         };
 
-        write!(fmt, "{}: {}", msg, self.message)
+        write!(fmt, "{}: {}", msg, self.message)?;
+
+        if let Some(line) = self.line {
+            write!(fmt, " (line {line}")?;
+            if let Some(ref statement) = self.statement {
+                write!(fmt, ", statement '{statement}'")?;
+            }
+            if let Some(ref token) = self.token {
+                write!(fmt, ", token '{token}'")?;
+            }
+            write!(fmt, ")")?;
+        } else if let Some(ref token) = self.token {
+            write!(fmt, " (token '{token}')")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -143,16 +227,23 @@ macro_rules! make_error {
             ),
         ))
     };
+    ($kind:ident, $message:expr, $token:expr) => {
+        return Err($crate::error::ObjError::Load(
+            $crate::error::LoadError::new_internal(
+                $crate::error::LoadErrorKind::$kind,
+                $message.to_string(),
+            )
+            .with_token($token),
+        ))
+    };
 }
 
 pub(crate) use make_error;
 
 pub(super) fn index_out_of_range<T, I>(index: usize) -> ObjResult<T> {
     let name = std::any::type_name::<I>();
-    Err(ObjError::Load(LoadError {
-        kind: LoadErrorKind::IndexOutOfRange,
-        message: format!(
-            "Given index type '{name}' is not large enough to contain the index '{index}'"
-        ),
-    }))
+    Err(ObjError::Load(LoadError::new_internal(
+        LoadErrorKind::IndexOutOfRange,
+        format!("Given index type '{name}' is not large enough to contain the index '{index}'"),
+    )))
 }
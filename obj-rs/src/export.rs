@@ -0,0 +1,162 @@
+//! Converts `Obj` back into `RawObj`, for writing it into a file
+
+use crate::error::make_error;
+use crate::raw::object::{Polygon, RawObj};
+use crate::{Obj, ObjResult, Position, TangentVertex, TexturedVertex, Vertex, raw};
+use num_traits::ToPrimitive;
+use std::collections::hash_map::{Entry, HashMap};
+use std::io::Write;
+
+/// Attributes of a vertex, which are written into the `.obj` format.
+///
+/// Implement this for your own vertex type to export `Obj`s of it.
+pub trait VertexAttributes {
+    /// Position vector of the vertex.
+    fn position(&self) -> [f32; 3];
+
+    /// Normal vector of the vertex, if it has any.
+    fn normal(&self) -> Option<[f32; 3]> {
+        None
+    }
+
+    /// Texture coordinates of the vertex, if it has any.
+    fn texture(&self) -> Option<[f32; 3]> {
+        None
+    }
+}
+
+impl VertexAttributes for Position {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexAttributes for Vertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+}
+
+impl VertexAttributes for TexturedVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+
+    fn texture(&self) -> Option<[f32; 3]> {
+        Some(self.texture)
+    }
+}
+
+impl VertexAttributes for TangentVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+
+    fn texture(&self) -> Option<[f32; 3]> {
+        Some(self.texture)
+    }
+}
+
+impl<V: VertexAttributes, I: ToPrimitive> Obj<V, I> {
+    /// Converts `Obj` into `RawObj`, whose polygons are the triangles of the index buffer.
+    ///
+    /// Equal positions, normals and texture coordinates are stored only once.
+    pub fn to_raw(&self) -> ObjResult<RawObj> {
+        if !self.indices.len().is_multiple_of(3) {
+            make_error!(
+                UntriangulatedModel,
+                "Expected the index buffer to consist of triangles"
+            );
+        }
+
+        let mut raw = RawObj {
+            name: self.name.clone(),
+            ..RawObj::default()
+        };
+        let mut positions = HashMap::new();
+        let mut normals = HashMap::new();
+        let mut tex_coords = HashMap::new();
+
+        // Every vertex gets the indices of its attributes
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let [x, y, z] = vertex.position();
+                let p = dedup(
+                    &mut positions,
+                    &mut raw.positions,
+                    (x, y, z, 1.0),
+                    [x, y, z],
+                );
+                let t = vertex.texture().map(|[u, v, w]| {
+                    dedup(&mut tex_coords, &mut raw.tex_coords, (u, v, w), [u, v, w])
+                });
+                let n = vertex
+                    .normal()
+                    .map(|[x, y, z]| dedup(&mut normals, &mut raw.normals, (x, y, z), [x, y, z]));
+                (p, t, n)
+            })
+            .collect::<Vec<_>>();
+
+        for triangle in self.indices.chunks(3) {
+            let mut corners = Vec::with_capacity(3);
+            for index in triangle {
+                match index.to_usize().and_then(|i| vertices.get(i)) {
+                    Some(&vertex) => corners.push(vertex),
+                    None => make_error!(
+                        IndexOutOfRange,
+                        "Index buffer refers to a vertex which does not exist"
+                    ),
+                }
+            }
+
+            // Attributes are written only if every corner of the triangle has them
+            let ptn = corners.iter().map(|&(p, t, n)| Some((p, t?, n?))).collect();
+            let pn = corners.iter().map(|&(p, _, n)| Some((p, n?))).collect();
+            let pt = corners.iter().map(|&(p, t, _)| Some((p, t?))).collect();
+            raw.polygons.push(match (ptn, pn, pt) {
+                (Some(vec), _, _) => Polygon::PTN(vec),
+                (_, Some(vec), _) => Polygon::PN(vec),
+                (_, _, Some(vec)) => Polygon::PT(vec),
+                _ => Polygon::P(corners.iter().map(|&(p, _, _)| p).collect()),
+            });
+        }
+
+        Ok(raw)
+    }
+
+    /// Writes `Obj` in wavefront `.obj` format. See `to_raw` for details.
+    pub fn write<W: Write>(&self, output: W) -> ObjResult<()> {
+        raw::write_obj(&self.to_raw()?, output)
+    }
+}
+
+/// Pushes `value` into `vec` unless an equal one has been pushed before, and returns its index.
+/// Values are compared bit by bit.
+fn dedup<T>(
+    cache: &mut HashMap<[u32; 3], usize>,
+    vec: &mut Vec<T>,
+    value: T,
+    key: [f32; 3],
+) -> usize {
+    match cache.entry(key.map(f32::to_bits)) {
+        Entry::Occupied(entry) => *entry.get(),
+        Entry::Vacant(entry) => {
+            vec.push(value);
+            *entry.insert(vec.len() - 1)
+        }
+    }
+}
@@ -26,15 +26,26 @@ dome.indices;
 #![deny(missing_docs)]
 
 mod error;
+mod export;
+mod normals;
 pub mod raw;
+mod scene;
+mod tangents;
+mod triangulation;
+mod vector;
 
 pub use crate::error::{LoadError, LoadErrorKind, ObjError, ObjResult};
+pub use crate::export::VertexAttributes;
+pub use crate::normals::NormalGeneration;
+pub use crate::scene::{Model, Scene, load_scene, load_scene_with_options};
+pub use crate::triangulation::Triangulation;
 
 use crate::error::{index_out_of_range, make_error};
-use crate::raw::object::Polygon;
+use crate::raw::object::{CurveTechnique, Polygon, SurfaceTechnique};
 use num_traits::FromPrimitive;
 use std::collections::hash_map::{Entry, HashMap};
 use std::io::BufRead;
+use std::path::Path;
 
 #[cfg(feature = "glium")]
 use glium::implement_vertex;
@@ -43,8 +54,85 @@ use serde::{Deserialize, Serialize};
 
 /// Load a wavefront OBJ file into Rust & OpenGL friendly format.
 pub fn load_obj<V: FromRawVertex<I>, T: BufRead, I>(input: T) -> ObjResult<Obj<V, I>> {
-    let raw = raw::parse_obj(input)?;
-    Obj::new(raw)
+    load_obj_with_options(input, &LoadOptions::default())
+}
+
+/// Load a wavefront OBJ file into Rust & OpenGL friendly format, with the given options.
+pub fn load_obj_with_options<V: FromRawVertex<I>, T: BufRead, I>(
+    input: T,
+    options: &LoadOptions,
+) -> ObjResult<Obj<V, I>> {
+    let raw = raw::parse_obj_with_options(input, &options.parse)?;
+    Obj::with_options(raw, options)
+}
+
+/// Load a wavefront OBJ file at `path` into Rust & OpenGL friendly format.
+pub fn load_obj_file<V: FromRawVertex<I>, P: AsRef<Path>, I>(path: P) -> ObjResult<Obj<V, I>> {
+    load_obj_file_with_options(path, &LoadOptions::default())
+}
+
+/// Load a wavefront OBJ file at `path` into Rust & OpenGL friendly format, with the given options.
+pub fn load_obj_file_with_options<V: FromRawVertex<I>, P: AsRef<Path>, I>(
+    path: P,
+    options: &LoadOptions,
+) -> ObjResult<Obj<V, I>> {
+    let raw = raw::parse_obj_file_with_options(path, &options.parse)?;
+    Obj::with_options(raw, options)
+}
+
+/// Load a wavefront OBJ file at `path` into Rust & OpenGL friendly format, parsing directly from
+/// its memory-mapped bytes.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is being parsed, see `memmap2::Mmap::map`.
+#[cfg(feature = "memmap2")]
+pub unsafe fn load_obj_file_mapped<V: FromRawVertex<I>, P: AsRef<Path>, I>(
+    path: P,
+) -> ObjResult<Obj<V, I>> {
+    unsafe { load_obj_file_mapped_with_options(path, &LoadOptions::default()) }
+}
+
+/// Load a wavefront OBJ file at `path` into Rust & OpenGL friendly format, parsing directly from
+/// its memory-mapped bytes, with the given options.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is being parsed, see `memmap2::Mmap::map`.
+#[cfg(feature = "memmap2")]
+pub unsafe fn load_obj_file_mapped_with_options<V: FromRawVertex<I>, P: AsRef<Path>, I>(
+    path: P,
+    options: &LoadOptions,
+) -> ObjResult<Obj<V, I>> {
+    let raw = unsafe { raw::parse_obj_file_mapped_with_options(path, &options.parse)? };
+    Obj::with_options(raw, options)
+}
+
+/// Options for converting `RawObj` into `Obj`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LoadOptions {
+    /// Options for parsing, used by `load_obj_with_options`.
+    pub parse: raw::ParseOptions,
+    /// Tessellation density of free-form curves. `None` honors the `ctech` statements of the file.
+    pub curve_technique: Option<CurveTechnique>,
+    /// Tessellation density of free-form surfaces. `None` honors the `stech` statements of the
+    /// file.
+    pub surface_technique: Option<SurfaceTechnique>,
+    /// How to split polygons which are not triangles.
+    pub triangulation: Triangulation,
+    /// How to generate normals for polygons which do not have any.
+    pub normals: NormalGeneration,
+}
+
+impl LoadOptions {
+    /// Tessellates free-form elements, generates normals and triangulates polygons of `raw`, as
+    /// requested.
+    fn prepare(&self, raw: &mut raw::RawObj) -> ObjResult<()> {
+        raw::freeform::tessellate(raw, self.curve_technique, self.surface_technique)?;
+        normals::generate_normals(raw, self.normals);
+        triangulation::triangulate(raw, self.triangulation);
+        Ok(())
+    }
 }
 
 /// 3D model object loaded from wavefront OBJ.
@@ -62,6 +150,17 @@ pub struct Obj<V = Vertex, I = u16> {
 impl<V: FromRawVertex<I>, I> Obj<V, I> {
     /// Create `Obj` from `RawObj` object.
     pub fn new(raw: raw::RawObj) -> ObjResult<Self> {
+        Obj::with_options(raw, &LoadOptions::default())
+    }
+
+    /// Create `Obj` from `RawObj` object, with the given options.
+    ///
+    /// Free-form curves and surfaces are tessellated first, so they end up in the vertex and
+    /// index buffer like any other polygon. Missing normals are then generated, and polygons are
+    /// triangulated, if requested.
+    pub fn with_options(mut raw: raw::RawObj, options: &LoadOptions) -> ObjResult<Self> {
+        options.prepare(&mut raw)?;
+
         let (vertices, indices) =
             FromRawVertex::process(raw.positions, raw.normals, raw.tex_coords, raw.polygons)?;
 
@@ -257,18 +356,50 @@ unsafe impl bytemuck::Pod for TexturedVertex {}
 #[cfg(feature = "glium")]
 implement_vertex!(TexturedVertex, position, normal, texture);
 
+/// Vertex data type of `Obj` which contains position, normal, texture and tangent data of a
+/// vertex, for normal mapping.
+///
+/// Tangents are computed the way [MikkTSpace][] does, so normal maps baked by other tools shade
+/// correctly.
+///
+/// [MikkTSpace]: http://www.mikktspace.com/
+#[derive(Default, Copy, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "vulkano", repr(C))]
+pub struct TangentVertex {
+    /// Position vector of a vertex.
+    pub position: [f32; 3],
+    /// Normal vertor of a vertex.
+    pub normal: [f32; 3],
+    /// Texture of a vertex.
+    pub texture: [f32; 3],
+    /// Tangent vector of a vertex, along the direction of increasing U. The fourth component is
+    /// the handedness of the tangent space, so that the bitangent is
+    /// `tangent.w * cross(normal, tangent.xyz)`.
+    pub tangent: [f32; 4],
+}
+
+#[cfg(feature = "vulkano")]
+unsafe impl bytemuck::Zeroable for TangentVertex {}
+#[cfg(feature = "vulkano")]
+unsafe impl bytemuck::Pod for TangentVertex {}
+
+#[cfg(feature = "glium")]
+implement_vertex!(TangentVertex, position, normal, texture, tangent);
+
 #[cfg(feature = "vulkano")]
 #[allow(
     deprecated,
     reason = "Use of `impl_vertex` is indeed deprecated since vulkano 0.33.0 but it's required here for compatibility with older versions."
 )]
 mod vulkano_vertex_impls {
-    use super::{Position, TexturedVertex, Vertex};
+    use super::{Position, TangentVertex, TexturedVertex, Vertex};
     use vulkano::impl_vertex;
 
     impl_vertex!(Vertex, position, normal);
     impl_vertex!(Position, position);
     impl_vertex!(TexturedVertex, position, normal, texture);
+    impl_vertex!(TangentVertex, position, normal, texture, tangent);
 }
 
 impl<I: FromPrimitive + Copy> FromRawVertex<I> for TexturedVertex {
@@ -341,6 +472,17 @@ impl<I: FromPrimitive + Copy> FromRawVertex<I> for TexturedVertex {
     }
 }
 
+impl<I: FromPrimitive + Copy> FromRawVertex<I> for TangentVertex {
+    fn process(
+        positions: Vec<(f32, f32, f32, f32)>,
+        normals: Vec<(f32, f32, f32)>,
+        tex_coords: Vec<(f32, f32, f32)>,
+        polygons: Vec<Polygon>,
+    ) -> ObjResult<(Vec<Self>, Vec<I>)> {
+        tangents::process(positions, normals, tex_coords, polygons)
+    }
+}
+
 #[cfg(feature = "glium")]
 mod glium_support {
     use super::Obj;
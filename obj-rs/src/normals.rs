@@ -0,0 +1,133 @@
+//! Generates normals of polygons which do not have any
+
+use crate::raw::object::{Polygon, RawObj};
+use crate::triangulation::{len, newell_normal, positions};
+use crate::vector::{dot, safe_normalize};
+use std::collections::HashMap;
+
+/// Method of generating normals for polygons which do not have any.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum NormalGeneration {
+    /// Do not generate normals. Vertex formats which require normals reject polygons without
+    /// them with `InsufficientData`.
+    #[default]
+    Disabled,
+    /// Give every vertex of a polygon the normal of the polygon, regardless of smoothing groups.
+    Flat,
+    /// Average the normals of the polygons sharing a vertex within the same smoothing group,
+    /// weighted by the area of each polygon. Polygons without a smoothing group (`s off`) get
+    /// flat normals.
+    AreaWeighted,
+    /// Average the normals of the polygons sharing a vertex within the same smoothing group,
+    /// weighted by the angle of each polygon at the vertex. Polygons without a smoothing group
+    /// (`s off`) get flat normals.
+    AngleWeighted,
+}
+
+/// Adds normals to every polygon of `raw` which does not have any. Polygons which already have
+/// normals are left untouched.
+pub(crate) fn generate_normals(raw: &mut RawObj, method: NormalGeneration) {
+    if method == NormalGeneration::Disabled || raw.polygons.iter().all(has_normals) {
+        return;
+    }
+
+    // `smoothing[i]` is the smoothing group of the `i`th polygon
+    let mut smoothing = vec![None; raw.polygons.len()];
+    if method != NormalGeneration::Flat {
+        for (&key, group) in &raw.smoothing_groups {
+            for range in &group.polygons {
+                smoothing[range.start..range.end].fill(Some(key));
+            }
+        }
+    }
+
+    // Sum up the weighted normals of every position, separately for each smoothing group
+    let mut sums = HashMap::<(usize, usize), [f32; 3]>::new();
+    for (polygon, &group) in raw.polygons.iter().zip(&smoothing) {
+        let Some(group) = group else { continue };
+        if has_normals(polygon) {
+            continue;
+        }
+
+        let points = positions(&raw.positions, polygon);
+        let normal = newell_normal(&points);
+        for (k, index) in position_indices(polygon).into_iter().enumerate() {
+            let weighted = match method {
+                NormalGeneration::AngleWeighted => {
+                    let angle = angle(&points, k);
+                    safe_normalize(normal).map(|x| x * angle)
+                }
+                // The length of Newell's normal is proportional to the area of the polygon
+                _ => normal,
+            };
+            let sum = sums.entry((group, index)).or_default();
+            for axis in 0..3 {
+                sum[axis] += weighted[axis];
+            }
+        }
+    }
+
+    // Assign the normals, sharing one normal per position and smoothing group
+    let mut shared = HashMap::<(usize, usize), usize>::new();
+    for (polygon, &group) in raw.polygons.iter_mut().zip(&smoothing) {
+        if has_normals(polygon) {
+            continue;
+        }
+
+        let normals = match group {
+            Some(group) => position_indices(polygon)
+                .into_iter()
+                .map(|index| {
+                    *shared.entry((group, index)).or_insert_with(|| {
+                        push(&mut raw.normals, safe_normalize(sums[&(group, index)]))
+                    })
+                })
+                .collect(),
+            None => {
+                let normal = newell_normal(&positions(&raw.positions, polygon));
+                vec![push(&mut raw.normals, safe_normalize(normal)); len(polygon)]
+            }
+        };
+        *polygon = with_normals(polygon, &normals);
+    }
+}
+
+fn has_normals(polygon: &Polygon) -> bool {
+    matches!(polygon, Polygon::PN(_) | Polygon::PTN(_))
+}
+
+fn position_indices(polygon: &Polygon) -> Vec<usize> {
+    match polygon {
+        Polygon::P(vec) => vec.clone(),
+        Polygon::PT(vec) | Polygon::PN(vec) => vec.iter().map(|&(p, _)| p).collect(),
+        Polygon::PTN(vec) => vec.iter().map(|&(p, _, _)| p).collect(),
+    }
+}
+
+/// Attaches the given normal indices to a polygon without normals.
+fn with_normals(polygon: &Polygon, normals: &[usize]) -> Polygon {
+    match polygon {
+        Polygon::P(vec) => Polygon::PN(vec.iter().copied().zip(normals.iter().copied()).collect()),
+        Polygon::PT(vec) => Polygon::PTN(
+            vec.iter()
+                .zip(normals)
+                .map(|(&(p, t), &n)| (p, t, n))
+                .collect(),
+        ),
+        Polygon::PN(_) | Polygon::PTN(_) => polygon.clone(),
+    }
+}
+
+/// Interior angle of a polygon at its `k`th corner.
+fn angle(points: &[[f32; 3]], k: usize) -> f32 {
+    let n = points.len();
+    let (prev, curr, next) = (points[(k + n - 1) % n], points[k], points[(k + 1) % n]);
+    let a = safe_normalize([0, 1, 2].map(|i| prev[i] - curr[i]));
+    let b = safe_normalize([0, 1, 2].map(|i| next[i] - curr[i]));
+    dot(a, b).clamp(-1.0, 1.0).acos()
+}
+
+fn push(normals: &mut Vec<(f32, f32, f32)>, [x, y, z]: [f32; 3]) -> usize {
+    normals.push((x, y, z));
+    normals.len() - 1
+}
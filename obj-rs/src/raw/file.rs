@@ -0,0 +1,80 @@
+//! Parses `.obj` and `.mtl` files from the file system
+
+use crate::error::ObjResult;
+use crate::raw::{
+    ParseOptions, RawMtl, RawObj, parse_mtl_bytes_with_options, parse_obj_bytes_with_options,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Parses a wavefront `.obj` file.
+///
+/// The directory of the file is stored in `RawObj::directory`, which relative paths of `mtllib`
+/// statements are resolved against.
+pub fn parse_obj_file<P: AsRef<Path>>(path: P) -> ObjResult<RawObj> {
+    parse_obj_file_with_options(path, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.obj` file, with the given options.
+pub fn parse_obj_file_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+) -> ObjResult<RawObj> {
+    let path = path.as_ref();
+    let mut obj = parse_obj_bytes_with_options(&fs::read(path)?, options)?;
+    obj.directory = Some(directory(path));
+    Ok(obj)
+}
+
+/// Parses a wavefront `.obj` file directly from its memory-mapped bytes, which saves copying the
+/// whole file into memory.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is being parsed, see `memmap2::Mmap::map`.
+#[cfg(feature = "memmap2")]
+pub unsafe fn parse_obj_file_mapped<P: AsRef<Path>>(path: P) -> ObjResult<RawObj> {
+    unsafe { parse_obj_file_mapped_with_options(path, &ParseOptions::default()) }
+}
+
+/// Parses a wavefront `.obj` file directly from its memory-mapped bytes, with the given options.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is being parsed, see `memmap2::Mmap::map`.
+#[cfg(feature = "memmap2")]
+pub unsafe fn parse_obj_file_mapped_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+) -> ObjResult<RawObj> {
+    let path = path.as_ref();
+    let file = fs::File::open(path)?;
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    let mut obj = parse_obj_bytes_with_options(&map, options)?;
+    obj.directory = Some(directory(path));
+    Ok(obj)
+}
+
+/// Parses a wavefront `.mtl` file *(incomplete)*.
+///
+/// The directory of the file is stored in `RawMtl::directory`, which relative paths of texture
+/// maps are resolved against.
+pub fn parse_mtl_file<P: AsRef<Path>>(path: P) -> ObjResult<RawMtl> {
+    parse_mtl_file_with_options(path, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.mtl` file *(incomplete)*, with the given options.
+pub fn parse_mtl_file_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+) -> ObjResult<RawMtl> {
+    let path = path.as_ref();
+    let mut mtl = parse_mtl_bytes_with_options(&fs::read(path)?, options)?;
+    mtl.directory = Some(directory(path));
+    Ok(mtl)
+}
+
+/// Returns the directory which contains the file at `path`.
+fn directory(path: &Path) -> PathBuf {
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
@@ -0,0 +1,815 @@
+//! Evaluates free-form curves and surfaces, and tessellates them into lines and polygons
+
+use crate::error::{ObjResult, make_error};
+use crate::raw::object::{
+    Curve, Curve2, CurveTechnique, CurveType, FreeFormAttributes, Line, Polygon, Range, RawObj,
+    Surface, SurfaceTechnique, TrimCurve,
+};
+use crate::vector::{cross, dot, sub};
+
+/// Curve technique used when neither the file nor the caller specifies one.
+const DEFAULT_CURVE_TECHNIQUE: CurveTechnique = CurveTechnique::Parametric { resolution: 4.0 };
+
+/// Surface technique used when neither the file nor the caller specifies one.
+const DEFAULT_SURFACE_TECHNIQUE: SurfaceTechnique = SurfaceTechnique::ParametricA {
+    resolution_u: 4.0,
+    resolution_v: 4.0,
+};
+
+/// Upper bound of the number of subdivisions in a single parametric direction.
+const MAX_DIVISIONS: usize = 1024;
+
+/// Upper bound of the degree in a single parametric direction, which keeps basis matrices small
+/// and their coefficients finite.
+const MAX_DEGREE: usize = 64;
+
+/// Basis matrix of the cardinal (Catmull-Rom) spline, in row-major order.
+const CARDINAL_MATRIX: [f32; 16] = [
+    0.0, -0.5, 1.0, -0.5, //
+    1.0, 0.0, -2.5, 1.5, //
+    0.0, 0.5, 2.0, -1.5, //
+    0.0, 0.0, -0.5, 0.5,
+];
+
+/// Tessellates every free-form curve and surface of `raw`.
+///
+/// Curves are appended to `lines` as polylines and surfaces are appended to `polygons` as
+/// triangles with texture coordinates and normals, so that they can be consumed like any other
+/// polygonal data. Generated elements join every group, material, smoothing group and merging
+/// group of the curve or surface they came from.
+///
+/// The density of the tessellation is decided by the `ctech` and `stech` statements of the file,
+/// unless `curve_technique` or `surface_technique` is given. Trimming loops and holes are honored
+/// per triangle, so the trimmed edges are as coarse as the tessellation itself.
+pub fn tessellate(
+    raw: &mut RawObj,
+    curve_technique: Option<CurveTechnique>,
+    surface_technique: Option<SurfaceTechnique>,
+) -> ObjResult<()> {
+    for index in 0..raw.curves.len() {
+        let start = raw.lines.len();
+        let line = tessellate_curve(raw, index, curve_technique)?;
+        raw.lines.push(line);
+
+        let range = Range {
+            start,
+            end: raw.lines.len(),
+        };
+        raw.for_each_group(|group| {
+            if contains(&group.curves, index) {
+                push_range(&mut group.lines, range);
+            }
+        });
+    }
+
+    for index in 0..raw.surfaces.len() {
+        let start = raw.polygons.len();
+        let triangles = tessellate_surface(raw, index, surface_technique)?;
+        raw.polygons.extend(triangles);
+
+        let range = Range {
+            start,
+            end: raw.polygons.len(),
+        };
+        raw.for_each_group(|group| {
+            if contains(&group.surfaces, index) {
+                push_range(&mut group.polygons, range);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Evaluates the position of a free-form curve at the parameter `t`.
+pub fn evaluate_curve(raw: &RawObj, curve: &Curve, t: f32) -> ObjResult<[f32; 3]> {
+    Ok(Patch::curve(raw, curve)?.evaluate(t, 0.0))
+}
+
+/// Evaluates the position of a 2D curve, in the parameter space of a surface, at the parameter
+/// `t`.
+pub fn evaluate_curve2(raw: &RawObj, curve: &Curve2, t: f32) -> ObjResult<[f32; 2]> {
+    let [u, v, _] = Patch::curve2(raw, curve)?.evaluate(t, 0.0);
+    Ok([u, v])
+}
+
+/// Evaluates the position of a free-form surface at the parameters `(u, v)`.
+pub fn evaluate_surface(raw: &RawObj, surface: &Surface, u: f32, v: f32) -> ObjResult<[f32; 3]> {
+    Ok(Patch::surface(raw, surface)?.evaluate(u, v))
+}
+
+/// Appends the vertices of a tessellated curve to `raw`, and returns the polyline.
+fn tessellate_curve(
+    raw: &mut RawObj,
+    index: usize,
+    technique: Option<CurveTechnique>,
+) -> ObjResult<Line> {
+    let curve = &raw.curves[index];
+    let patch = Patch::curve(raw, curve)?;
+    let technique = technique
+        .or(curve.technique)
+        .unwrap_or(DEFAULT_CURVE_TECHNIQUE);
+    let divisions = patch.divisions(technique_for_curve(technique), false);
+    let (start, end) = curve.range;
+
+    raw.positions.reserve(divisions + 1);
+
+    let mut line = Vec::with_capacity(divisions + 1);
+    for i in 0..=divisions {
+        let t = lerp(start, end, i as f32 / divisions as f32);
+        let [x, y, z] = patch.evaluate(t, 0.0);
+        line.push(raw.positions.len());
+        raw.positions.push((x, y, z, 1.0));
+    }
+
+    Ok(Line::P(line))
+}
+
+/// Appends the vertices of a tessellated surface to `raw`, and returns the triangles.
+fn tessellate_surface(
+    raw: &mut RawObj,
+    index: usize,
+    technique: Option<SurfaceTechnique>,
+) -> ObjResult<Vec<Polygon>> {
+    let surface = &raw.surfaces[index];
+    let patch = Patch::surface(raw, surface)?;
+    let technique = technique
+        .or(surface.technique)
+        .unwrap_or(DEFAULT_SURFACE_TECHNIQUE);
+    let (technique_u, technique_v) = technique_for_surface(technique);
+    let divisions_u = patch.divisions(technique_u, false);
+    let divisions_v = patch.divisions(technique_v, true);
+
+    // Texture coordinates are evaluated from the texture control points only if every control
+    // point has one, otherwise the normalized parameters are used.
+    let tex_coords = surface
+        .vertices
+        .iter()
+        .map(|&(_, t, _)| {
+            t.and_then(|t| raw.tex_coords.get(t))
+                .map(|&(u, v, w)| [u, v, w])
+        })
+        .collect::<Option<Vec<_>>>();
+
+    let trims = trimming_loops(raw, &surface.trims)?;
+    let holes = trimming_loops(raw, &surface.holes)?;
+
+    let (u0, u1) = surface.range_u;
+    let (v0, v1) = surface.range_v;
+
+    let count = (divisions_u + 1) * (divisions_v + 1);
+    raw.positions.reserve(count);
+    raw.normals.reserve(count);
+    raw.tex_coords.reserve(count);
+    let base_position = raw.positions.len();
+    let base_tex_coord = raw.tex_coords.len();
+    let base_normal = raw.normals.len();
+
+    let mut params = Vec::with_capacity(count);
+    for j in 0..=divisions_v {
+        for i in 0..=divisions_u {
+            let s = i as f32 / divisions_u as f32;
+            let t = j as f32 / divisions_v as f32;
+            let (u, v) = (lerp(u0, u1, s), lerp(v0, v1, t));
+
+            let [x, y, z] = patch.evaluate(u, v);
+            let [nx, ny, nz] = patch.normal(u, v);
+            let texture = match tex_coords {
+                Some(ref tex_coords) => patch.combine(u, v, |i| tex_coords[i]),
+                None => [s, t, 0.0],
+            };
+
+            raw.positions.push((x, y, z, 1.0));
+            raw.normals.push((nx, ny, nz));
+            raw.tex_coords.push((texture[0], texture[1], texture[2]));
+            params.push((u, v));
+        }
+    }
+
+    let stride = divisions_u + 1;
+    let vertex = |i: usize| (base_position + i, base_tex_coord + i, base_normal + i);
+    let keep = |corners: [usize; 3]| {
+        let (u, v) = corners
+            .iter()
+            .fold((0.0, 0.0), |(u, v), &i| (u + params[i].0, v + params[i].1));
+        let centroid = (u / 3.0, v / 3.0);
+        (trims.is_empty() || is_inside(&trims, centroid)) && !is_inside(&holes, centroid)
+    };
+
+    let mut triangles = Vec::with_capacity(divisions_u * divisions_v * 2);
+    for j in 0..divisions_v {
+        for i in 0..divisions_u {
+            let i00 = j * stride + i;
+            let i10 = i00 + 1;
+            let i01 = i00 + stride;
+            let i11 = i01 + 1;
+
+            for corners in [[i00, i10, i11], [i00, i11, i01]] {
+                if keep(corners) {
+                    triangles.push(Polygon::PTN(corners.iter().map(|&i| vertex(i)).collect()));
+                }
+            }
+        }
+    }
+
+    Ok(triangles)
+}
+
+/// Evaluates trimming loops into closed polylines in the parameter space of a surface.
+fn trimming_loops(raw: &RawObj, loops: &[Vec<TrimCurve>]) -> ObjResult<Vec<Vec<(f32, f32)>>> {
+    loops
+        .iter()
+        .map(|trim_loop| {
+            let mut polyline = Vec::new();
+            for segment in trim_loop {
+                let curve = match raw.curves2.get(segment.curve) {
+                    Some(curve) => curve,
+                    None => make_error!(IndexOutOfRange, "Too big index value"),
+                };
+                let patch = Patch::curve2(raw, curve)?;
+                let divisions =
+                    patch.divisions(technique_for_curve(DEFAULT_CURVE_TECHNIQUE), false);
+
+                for i in 0..=divisions {
+                    let t = lerp(segment.start, segment.end, i as f32 / divisions as f32);
+                    let [u, v, _] = patch.evaluate(t, 0.0);
+                    polyline.push((u, v));
+                }
+            }
+            Ok(polyline)
+        })
+        .collect()
+}
+
+/// Tests whether a point is inside of the given closed polylines, using the even-odd rule.
+fn is_inside(polylines: &[Vec<(f32, f32)>], (x, y): (f32, f32)) -> bool {
+    let mut inside = false;
+    for polyline in polylines {
+        let len = polyline.len();
+        for i in 0..len {
+            let (x0, y0) = polyline[i];
+            let (x1, y1) = polyline[(i + 1) % len];
+            if (y0 > y) != (y1 > y) && x < x0 + (y - y0) * (x1 - x0) / (y1 - y0) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Subdivision rule of a single parametric direction.
+#[derive(Copy, Clone)]
+enum Technique {
+    Parametric(f32),
+    Space(f32),
+    Curvature(f32, f32),
+}
+
+fn technique_for_curve(technique: CurveTechnique) -> Technique {
+    match technique {
+        CurveTechnique::Parametric { resolution } => Technique::Parametric(resolution),
+        CurveTechnique::Space { max_length } => Technique::Space(max_length),
+        CurveTechnique::Curvature {
+            max_distance,
+            max_angle,
+        } => Technique::Curvature(max_distance, max_angle),
+    }
+}
+
+fn technique_for_surface(technique: SurfaceTechnique) -> (Technique, Technique) {
+    match technique {
+        SurfaceTechnique::ParametricA {
+            resolution_u,
+            resolution_v,
+        } => (
+            Technique::Parametric(resolution_u),
+            Technique::Parametric(resolution_v),
+        ),
+        SurfaceTechnique::ParametricB { resolution } => (
+            Technique::Parametric(resolution),
+            Technique::Parametric(resolution),
+        ),
+        SurfaceTechnique::Space { max_length } => {
+            (Technique::Space(max_length), Technique::Space(max_length))
+        }
+        SurfaceTechnique::Curvature {
+            max_distance,
+            max_angle,
+        } => (
+            Technique::Curvature(max_distance, max_angle),
+            Technique::Curvature(max_distance, max_angle),
+        ),
+    }
+}
+
+/// Converts a possibly fractional, infinite or NaN number of subdivisions into a usable one.
+fn to_divisions(value: f32) -> usize {
+    if value.is_nan() {
+        1
+    } else {
+        (value.ceil() as usize).clamp(1, MAX_DIVISIONS)
+    }
+}
+
+/// Control points of a curve or a surface, prepared for evaluation.
+struct Patch {
+    basis_u: Basis,
+    /// `None` for curves.
+    basis_v: Option<Basis>,
+    rational: bool,
+    /// Control points and their weights, the `u` direction varies fastest.
+    points: Vec<[f32; 4]>,
+}
+
+impl Patch {
+    fn curve(raw: &RawObj, curve: &Curve) -> ObjResult<Self> {
+        let points = curve
+            .vertices
+            .iter()
+            .map(|&i| match raw.positions.get(i) {
+                Some(&(x, y, z, w)) => Ok([x, y, z, w]),
+                None => make_error!(IndexOutOfRange, "Too big index value"),
+            })
+            .collect::<ObjResult<_>>()?;
+
+        Patch::new(&curve.attributes, &curve.parameters, None, points)
+    }
+
+    fn curve2(raw: &RawObj, curve: &Curve2) -> ObjResult<Self> {
+        let points = curve
+            .vertices
+            .iter()
+            .map(|&i| match raw.param_vertices.get(i) {
+                Some(&(u, v, w)) => Ok([u, v, 0.0, w]),
+                None => make_error!(IndexOutOfRange, "Too big index value"),
+            })
+            .collect::<ObjResult<_>>()?;
+
+        Patch::new(&curve.attributes, &curve.parameters, None, points)
+    }
+
+    fn surface(raw: &RawObj, surface: &Surface) -> ObjResult<Self> {
+        let points = surface
+            .vertices
+            .iter()
+            .map(|&(i, _, _)| match raw.positions.get(i) {
+                Some(&(x, y, z, w)) => Ok([x, y, z, w]),
+                None => make_error!(IndexOutOfRange, "Too big index value"),
+            })
+            .collect::<ObjResult<_>>()?;
+
+        Patch::new(
+            &surface.attributes,
+            &surface.parameters_u,
+            Some(&surface.parameters_v),
+            points,
+        )
+    }
+
+    fn new(
+        attributes: &FreeFormAttributes,
+        parameters_u: &[f32],
+        parameters_v: Option<&[f32]>,
+        points: Vec<[f32; 4]>,
+    ) -> ObjResult<Self> {
+        // Every basis needs at least `degree + 1` control points in its direction, so degrees are
+        // checked before any basis matrix is built
+        let (degree_u, degree_v) = attributes.degree;
+        let degree_v = if parameters_v.is_some() { degree_v } else { 0 };
+        if degree_u.max(degree_v) > MAX_DEGREE {
+            make_error!(
+                InsufficientData,
+                format!("Degree should be at most {}", MAX_DEGREE)
+            )
+        }
+        if (degree_u + 1) * (degree_v + 1) > points.len() {
+            make_error!(
+                InsufficientData,
+                "Expected at least degree + 1 control points in each direction"
+            )
+        }
+
+        let basis_u = Basis::new(attributes, false, parameters_u)?;
+        let basis_v = match parameters_v {
+            Some(parameters_v) => Some(Basis::new(attributes, true, parameters_v)?),
+            None => None,
+        };
+
+        let expected = basis_u.count() * basis_v.as_ref().map_or(1, Basis::count);
+        if points.len() != expected {
+            make_error!(
+                InsufficientData,
+                format!(
+                    "Expected {} control points for the given degree and parameter values, but {} were given",
+                    expected,
+                    points.len()
+                )
+            )
+        }
+
+        Ok(Patch {
+            basis_u,
+            basis_v,
+            rational: attributes.rational,
+            points,
+        })
+    }
+
+    /// Evaluates the position at the parameters `(u, v)`. `v` is ignored for curves.
+    fn evaluate(&self, u: f32, v: f32) -> [f32; 3] {
+        self.combine(u, v, |i| {
+            let [x, y, z, _] = self.points[i];
+            [x, y, z]
+        })
+    }
+
+    /// Combines per-control-point values with the basis functions evaluated at `(u, v)`.
+    fn combine(&self, u: f32, v: f32, value: impl Fn(usize) -> [f32; 3]) -> [f32; 3] {
+        let (first_u, weights_u) = self.basis_u.evaluate(u);
+        let (first_v, weights_v) = match self.basis_v {
+            Some(ref basis) => basis.evaluate(v),
+            None => (0, vec![1.0]),
+        };
+        let count_u = self.basis_u.count();
+
+        let mut sum = [0.0; 3];
+        let mut total = 0.0;
+        for (j, weight_v) in weights_v.iter().enumerate() {
+            for (i, weight_u) in weights_u.iter().enumerate() {
+                let index = (first_v + j) * count_u + first_u + i;
+                let mut weight = weight_u * weight_v;
+                if self.rational {
+                    weight *= self.points[index][3];
+                }
+
+                let value = value(index);
+                for k in 0..3 {
+                    sum[k] += weight * value[k];
+                }
+                total += weight;
+            }
+        }
+
+        if self.rational && total != 0.0 {
+            sum.map(|x| x / total)
+        } else {
+            sum
+        }
+    }
+
+    /// Approximates the unit normal vector at the parameters `(u, v)` with central differences.
+    fn normal(&self, u: f32, v: f32) -> [f32; 3] {
+        let (u0, u1) = self.basis_u.domain();
+        let (v0, v1) = self.basis_v.as_ref().map_or((0.0, 1.0), Basis::domain);
+        let h_u = (u1 - u0).abs().max(f32::EPSILON) * 1e-3;
+        let h_v = (v1 - v0).abs().max(f32::EPSILON) * 1e-3;
+
+        // Degenerated points such as poles have no tangent plane, so the normal of a nearby point
+        // is used instead.
+        let (mut u, mut v) = (u, v);
+        for _ in 0..8 {
+            let du = sub(
+                self.evaluate((u + h_u).min(u1), v),
+                self.evaluate((u - h_u).max(u0), v),
+            );
+            let dv = sub(
+                self.evaluate(u, (v + h_v).min(v1)),
+                self.evaluate(u, (v - h_v).max(v0)),
+            );
+            let n = cross(du, dv);
+            let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            if len > 0.0 {
+                return n.map(|x| x / len);
+            }
+
+            u += ((u0 + u1) / 2.0 - u) * 0.1;
+            v += ((v0 + v1) / 2.0 - v) * 0.1;
+        }
+        [0.0, 0.0, 1.0]
+    }
+
+    /// Decides the number of subdivisions in a single direction.
+    fn divisions(&self, technique: Technique, v_direction: bool) -> usize {
+        let basis = match (v_direction, &self.basis_v) {
+            (true, Some(basis)) => basis,
+            _ => &self.basis_u,
+        };
+
+        match technique {
+            Technique::Parametric(resolution) => {
+                to_divisions(basis.segments() as f32 * resolution * basis.degree().max(1) as f32)
+            }
+            Technique::Space(max_length) => {
+                let (length, _) = self.measure(v_direction);
+                to_divisions(length / max_length)
+            }
+            Technique::Curvature(max_distance, max_angle) => {
+                // A circular arc of length `l` which turns `θ` radians deviates from its chord by
+                // about `lθ/8n²` when it is split into `n` segments.
+                let (length, turning) = self.measure(v_direction);
+                let by_angle = to_divisions(turning / max_angle.to_radians());
+                let by_distance = to_divisions((length * turning / (8.0 * max_distance)).sqrt());
+                by_angle.max(by_distance)
+            }
+        }
+    }
+
+    /// Measures the longest length and the largest total turning angle of the control polygons
+    /// along a direction.
+    fn measure(&self, v_direction: bool) -> (f32, f32) {
+        let count_u = self.basis_u.count();
+        let count_v = self.points.len() / count_u;
+        let (rows, columns) = if v_direction {
+            (count_u, count_v)
+        } else {
+            (count_v, count_u)
+        };
+
+        let mut result = (0.0f32, 0.0f32);
+        for row in 0..rows {
+            let point = |column: usize| {
+                let index = if v_direction {
+                    column * count_u + row
+                } else {
+                    row * count_u + column
+                };
+                let [x, y, z, _] = self.points[index];
+                [x, y, z]
+            };
+
+            let mut length = 0.0;
+            let mut turning = 0.0;
+            let mut previous: Option<[f32; 3]> = None;
+            for column in 1..columns {
+                let edge = sub(point(column), point(column - 1));
+                let len = dot(edge, edge).sqrt();
+                if len == 0.0 {
+                    continue;
+                }
+                length += len;
+                if let Some(prev) = previous {
+                    let cos = dot(prev, edge) / (dot(prev, prev).sqrt() * len);
+                    turning += cos.clamp(-1.0, 1.0).acos();
+                }
+                previous = Some(edge);
+            }
+
+            result = (result.0.max(length), result.1.max(turning));
+        }
+        result
+    }
+}
+
+/// Basis functions of a single parametric direction.
+enum Basis {
+    /// Piecewise polynomial defined by a basis matrix, which is used by every type except
+    /// B-spline.
+    Matrix {
+        degree: usize,
+        step: usize,
+        /// `(degree + 1)²` coefficients, row `i` holds the coefficients of `tⁿ` of the `i`th
+        /// basis function.
+        matrix: Vec<f32>,
+        knots: Vec<f32>,
+    },
+    /// B-spline basis defined by a knot vector.
+    BSpline { degree: usize, knots: Vec<f32> },
+}
+
+impl Basis {
+    fn new(attributes: &FreeFormAttributes, v_direction: bool, knots: &[f32]) -> ObjResult<Self> {
+        let (degree, matrix, step) = if v_direction {
+            let (_, degree) = attributes.degree;
+            (degree, &attributes.basis_matrix.1, attributes.step.1)
+        } else {
+            let (degree, _) = attributes.degree;
+            (degree, &attributes.basis_matrix.0, attributes.step.0)
+        };
+
+        if knots.len() < 2 {
+            make_error!(
+                InsufficientData,
+                "Expected at least 2 global parameter values specified with 'parm'"
+            )
+        }
+        if knots.iter().any(|knot| !knot.is_finite()) {
+            make_error!(
+                WrongTypeOfArguments,
+                "Global parameter values should be finite numbers"
+            )
+        }
+        if knots.windows(2).any(|pair| pair[0] > pair[1]) {
+            make_error!(
+                InsufficientData,
+                "Global parameter values should never decrease"
+            )
+        }
+        let knots = knots.to_vec();
+
+        Ok(match attributes.curve_type {
+            CurveType::BSpline => {
+                if knots.len() < 2 * degree + 2 {
+                    make_error!(
+                        InsufficientData,
+                        "Expected at least 2 * (degree + 1) knots for B-spline"
+                    )
+                }
+                Basis::BSpline { degree, knots }
+            }
+            CurveType::Bezier => Basis::Matrix {
+                degree,
+                step: degree.max(1),
+                matrix: bezier_matrix(degree),
+                knots,
+            },
+            CurveType::Cardinal => {
+                if degree != 3 {
+                    make_error!(InsufficientData, "Cardinal splines should be of degree 3")
+                }
+                Basis::Matrix {
+                    degree,
+                    step: 1,
+                    matrix: CARDINAL_MATRIX.to_vec(),
+                    knots,
+                }
+            }
+            CurveType::Taylor => Basis::Matrix {
+                degree,
+                step: degree + 1,
+                matrix: taylor_matrix(degree),
+                knots,
+            },
+            CurveType::BasisMatrix => {
+                if matrix.len() != (degree + 1) * (degree + 1) {
+                    make_error!(
+                        InsufficientData,
+                        "Expected a (degree + 1) by (degree + 1) basis matrix specified with 'bmat'"
+                    )
+                }
+                if step == 0 {
+                    make_error!(InsufficientData, "Step size should be at least 1")
+                }
+                Basis::Matrix {
+                    degree,
+                    step,
+                    matrix: matrix.clone(),
+                    knots,
+                }
+            }
+        })
+    }
+
+    fn degree(&self) -> usize {
+        match *self {
+            Basis::Matrix { degree, .. } | Basis::BSpline { degree, .. } => degree,
+        }
+    }
+
+    /// Returns the number of control points required by this basis.
+    fn count(&self) -> usize {
+        match *self {
+            Basis::Matrix {
+                degree,
+                step,
+                ref knots,
+                ..
+            } => step * (knots.len() - 2) + degree + 1,
+            Basis::BSpline { degree, ref knots } => knots.len() - degree - 1,
+        }
+    }
+
+    /// Returns the number of non-empty polynomial segments.
+    fn segments(&self) -> usize {
+        match *self {
+            Basis::Matrix { ref knots, .. } => knots.len() - 1,
+            Basis::BSpline { degree, ref knots } => (degree..self.count())
+                .filter(|&k| knots[k] < knots[k + 1])
+                .count()
+                .max(1),
+        }
+    }
+
+    /// Returns the range of the parameter where this basis is defined.
+    fn domain(&self) -> (f32, f32) {
+        match *self {
+            Basis::Matrix { ref knots, .. } => (knots[0], knots[knots.len() - 1]),
+            Basis::BSpline { degree, ref knots } => (knots[degree], knots[self.count()]),
+        }
+    }
+
+    /// Evaluates the basis functions at `t`. Returns the index of the first control point which
+    /// affects `t`, and the values of the `degree + 1` basis functions from there.
+    fn evaluate(&self, t: f32) -> (usize, Vec<f32>) {
+        match *self {
+            Basis::Matrix {
+                degree,
+                step,
+                ref matrix,
+                ref knots,
+            } => {
+                let segments = knots.len() - 1;
+                let segment = (1..segments).take_while(|&j| knots[j] <= t).count();
+                let (k0, k1) = (knots[segment], knots[segment + 1]);
+                let s = if k1 != k0 { (t - k0) / (k1 - k0) } else { 0.0 };
+
+                let weights = matrix
+                    .chunks(degree + 1)
+                    .map(|row| row.iter().rev().fold(0.0, |acc, &c| acc * s + c))
+                    .collect();
+                (segment * step, weights)
+            }
+            Basis::BSpline { degree, ref knots } => {
+                // See "The NURBS Book", algorithm A2.2
+                let count = self.count();
+                let t = t.clamp(knots[degree], knots[count]);
+                let span = (degree..count)
+                    .rev()
+                    .find(|&k| knots[k] <= t && knots[k] < knots[k + 1])
+                    .unwrap_or(degree);
+
+                let mut weights = vec![0.0; degree + 1];
+                let mut left = vec![0.0; degree + 1];
+                let mut right = vec![0.0; degree + 1];
+                weights[0] = 1.0;
+                for j in 1..=degree {
+                    left[j] = t - knots[span + 1 - j];
+                    right[j] = knots[span + j] - t;
+                    let mut saved = 0.0;
+                    for r in 0..j {
+                        let denominator = right[r + 1] + left[j - r];
+                        let temp = if denominator != 0.0 {
+                            weights[r] / denominator
+                        } else {
+                            0.0
+                        };
+                        weights[r] = saved + right[r + 1] * temp;
+                        saved = left[j - r] * temp;
+                    }
+                    weights[j] = saved;
+                }
+                (span - degree, weights)
+            }
+        }
+    }
+}
+
+/// Returns the basis matrix of the Bernstein polynomials of the given degree.
+fn bezier_matrix(degree: usize) -> Vec<f32> {
+    let n = degree + 1;
+    let mut matrix = vec![0.0; n * n];
+    for i in 0..n {
+        for k in i..n {
+            let sign = if (k - i) % 2 == 0 { 1.0 } else { -1.0 };
+            matrix[i * n + k] = sign * binomial(degree, i) * binomial(degree - i, k - i);
+        }
+    }
+    matrix
+}
+
+/// Returns the basis matrix of the power basis of the given degree.
+fn taylor_matrix(degree: usize) -> Vec<f32> {
+    let n = degree + 1;
+    let mut matrix = vec![0.0; n * n];
+    for i in 0..n {
+        matrix[i * n + i] = 1.0;
+    }
+    matrix
+}
+
+fn binomial(n: usize, k: usize) -> f32 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f32 / (i + 1) as f32)
+}
+
+fn contains(ranges: &[Range], index: usize) -> bool {
+    ranges
+        .iter()
+        .any(|range| range.start <= index && index < range.end)
+}
+
+/// Appends a range, merging it with the last one if they are adjacent.
+fn push_range(ranges: &mut Vec<Range>, range: Range) {
+    if range.start == range.end {
+        return;
+    }
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[test]
+fn test_bezier_matrix() {
+    assert_eq!(
+        bezier_matrix(3),
+        [
+            1.0, -3.0, 3.0, -1.0, //
+            0.0, 3.0, -6.0, 3.0, //
+            0.0, 0.0, 3.0, -3.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]
+    );
+}
@@ -1,19 +1,34 @@
 use crate::error::{LoadError, LoadErrorKind, ObjError, ObjResult};
-use std::io::{BufRead, Lines, Result};
-use std::iter::Map;
+use std::io::{BufRead, Error, ErrorKind};
 
-fn strip_comment(mut line: String) -> String {
-    if let Some(idx) = line.find('#') {
-        line.truncate(idx)
+/// Finds the `#` which starts the comment of a line. A `#` of the special values which MSVC
+/// writes for floats, such as `1.#QNAN`, does not start a comment.
+pub(super) fn comment_start(line: &[u8]) -> Option<usize> {
+    const SPECIAL_VALUES: [&[u8]; 4] = [b"QNAN", b"SNAN", b"IND", b"INF"];
+
+    let mut from = 0;
+    loop {
+        let idx = from + line[from..].iter().position(|&b| b == b'#')?;
+        let after_number = matches!(line[..idx], [.., b'0'..=b'9', b'.']);
+        let special_value = SPECIAL_VALUES.iter().any(|value| {
+            line[idx + 1..]
+                .get(..value.len())
+                .is_some_and(|name| name.eq_ignore_ascii_case(value))
+        });
+        if !(after_number && special_value) {
+            return Some(idx);
+        }
+        from = idx + 1;
     }
-    line
 }
 
 #[test]
 fn test_strip_commect() {
     macro_rules! t {
         ($input:expr => $output:expr) => {
-            assert_eq!(strip_comment(String::from($input)), String::from($output));
+            let input: &str = $input;
+            let end = comment_start(input.as_bytes()).unwrap_or(input.len());
+            assert_eq!(&input[..end], $output);
         };
     }
 
@@ -21,86 +36,228 @@ fn test_strip_commect() {
     t!("abc # def" => "abc ");
     t!("한글 # 한글" => "한글 ");
     t!("" => "");
+    t!("v 1.#QNAN -1.#IND 1.#INF # 1.#INF" => "v 1.#QNAN -1.#IND 1.#INF ");
+    t!("v 1 2 3#1.#QNAN" => "v 1 2 3");
+    t!("v #QNAN" => "v ");
 }
 
-type StrippedLines<T> = Map<Lines<T>, fn(Result<String>) -> Result<String>>;
-
-#[derive(Debug)]
-pub struct Lexer<T> {
-    stripped_lines: StrippedLines<T>,
+/// Physical lines of a buffer in memory, borrowed from the buffer with their line terminators and
+/// comments stripped.
+pub(super) struct SliceLines<'a> {
+    input: &'a [u8],
+    /// Number of lines read so far.
+    pub(super) line: usize,
 }
 
-impl<T: BufRead> Lexer<T> {
-    pub fn new(input: T) -> Self {
-        Lexer {
-            stripped_lines: input.lines().map(|result| result.map(strip_comment)),
-        }
+impl<'a> SliceLines<'a> {
+    /// Constructs lines of `input`, numbered as if `line` lines preceded it.
+    pub(super) fn new(input: &'a [u8], line: usize) -> Self {
+        SliceLines { input, line }
     }
 }
 
-impl<T: BufRead> Iterator for Lexer<T> {
-    type Item = ObjResult<String>;
+impl<'a> Iterator for SliceLines<'a> {
+    type Item = ObjResult<&'a str>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Check if maybe_line has finished
-        let maybe_line = self.stripped_lines.next()?;
+        if self.input.is_empty() {
+            return None;
+        }
+        let (line, rest) = match self.input.iter().position(|&b| b == b'\n') {
+            Some(idx) => (&self.input[..idx], &self.input[idx + 1..]),
+            None => (self.input, &[][..]),
+        };
+        self.input = rest;
+        self.line += 1;
 
-        // Check if maybe_line has errored
-        let line = match maybe_line {
-            Err(e) => return Some(Err(ObjError::Io(e))),
-            Ok(val) => val,
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = match comment_start(line) {
+            Some(idx) => &line[..idx],
+            None => line,
         };
+        Some(str::from_utf8(line).map_err(|_| {
+            ObjError::Io(Error::new(
+                ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            ))
+        }))
+    }
+}
+
+/// Splits each logical line of `input` into a statement and its arguments, and calls `callback`
+/// with them and the line number. The last argument of `callback` is the text of the arguments as
+/// written, with its inner whitespace preserved.
+///
+/// Every token is borrowed from `input`, so no allocation happens per line.
+pub fn lex<F>(input: &[u8], callback: F) -> ObjResult<()>
+where
+    F: FnMut(usize, &str, &[&str], &str) -> ObjResult<()>,
+{
+    lex_at(input, 0, callback)
+}
+
+/// Same as `lex`, but numbers the lines of `input` as if `line` lines preceded it.
+pub fn lex_at<F>(input: &[u8], line: usize, mut callback: F) -> ObjResult<()>
+where
+    F: FnMut(usize, &str, &[&str], &str) -> ObjResult<()>,
+{
+    let mut lines = SliceLines::new(input, line);
 
-        // Merge lines connected with backslashes
-        let mut buffer = String::new();
-        match line.strip_suffix('\\') {
-            None => buffer.push_str(&line),
-            Some(stripped) => {
-                buffer.push_str(stripped);
-                buffer.push(' ');
-
-                // Search for the next lines
-                loop {
-                    let line = match self.stripped_lines.next() {
-                        None => {
-                            return Some(Err(ObjError::Load(LoadError::new_internal(
+    // Buffers which are reused by every statement
+    let mut words = Vec::new();
+    let mut merged = String::new();
+
+    while let Some(line) = lines.next() {
+        let number = lines.line;
+        let mut line = line?;
+        words.clear();
+
+        // Merge lines connected with backslashes. Words never span several lines, so only the
+        // text of the arguments needs to be copied.
+        let continued = line.ends_with('\\');
+        if continued {
+            merged.clear();
+            while let Some(stripped) = line.strip_suffix('\\') {
+                words.extend(stripped.split_whitespace());
+                merged.push_str(stripped);
+                merged.push(' ');
+
+                line = match lines.next() {
+                    None => {
+                        return Err(ObjError::Load(
+                            LoadError::new_internal(
                                 LoadErrorKind::BackslashAtEOF,
                                 "Expected a line, but met an EOF".to_string(),
-                            ))));
-                        }
-                        Some(Err(e)) => return Some(Err(ObjError::Io(e))),
-                        Some(Ok(val)) => val,
-                    };
-                    match line.strip_suffix('\\') {
-                        Some(stripped) => {
-                            buffer.push_str(stripped);
-                            buffer.push(' ');
-                        }
-                        None => {
-                            buffer.push_str(&line);
-                            break;
-                        }
+                            )
+                            .at(number, words.first().copied().unwrap_or("")),
+                        ));
                     }
-                }
+                    Some(line) => line?,
+                };
             }
+            merged.push_str(line);
         }
+        words.extend(line.split_whitespace());
 
-        Some(Ok(buffer))
+        if let Some((&stmt, args)) = words.split_first() {
+            let text = arguments(if continued { &merged } else { line });
+            callback(number, stmt, args, text).map_err(|e| e.at(number, stmt))?
+        }
     }
+
+    Ok(())
 }
 
-pub fn lex<T, F>(input: T, mut callback: F) -> ObjResult<()>
+/// Callback of `lex`, for parsers which are shared between several ways of lexing.
+pub(super) type Callback<'a> = dyn FnMut(usize, &str, &[&str], &str) -> ObjResult<()> + 'a;
+
+/// Same as `lex`, but reads `input` in blocks of about 64 KiB instead of all at once, so only a
+/// block is kept in memory at a time.
+pub fn lex_read<T, F>(input: T, mut callback: F) -> ObjResult<()>
 where
     T: BufRead,
-    F: FnMut(&str, &[&str]) -> ObjResult<()>,
+    F: FnMut(usize, &str, &[&str], &str) -> ObjResult<()>,
 {
-    for maybe_buffer in Lexer::new(input) {
-        if let [stmt, ref args @ ..] = maybe_buffer?.split_whitespace().collect::<Vec<_>>()[..] {
-            callback(stmt, args)?
+    let mut blocks = Blocks::new(input);
+    while let Some((line, block)) = blocks.next_block()? {
+        lex_at(block, line, &mut callback)?;
+    }
+    Ok(())
+}
+
+/// Number of bytes which are read before a block is lexed.
+const BLOCK_LEN: usize = 1 << 16;
+
+/// Reads an input in blocks which consist of whole logical lines, so that each of them can be
+/// lexed with `lex_at`.
+#[derive(Debug)]
+pub(super) struct Blocks<T> {
+    input: T,
+    /// Bytes which have been read, starting with the last block.
+    buffer: Vec<u8>,
+    /// Length of the last block.
+    len: usize,
+    /// Number of lines which precede the last block.
+    line: usize,
+    /// Whether the whole input has been read.
+    finished: bool,
+}
+
+impl<T: BufRead> Blocks<T> {
+    pub(super) fn new(input: T) -> Self {
+        Blocks {
+            input,
+            buffer: Vec::new(),
+            len: 0,
+            line: 0,
+            finished: false,
         }
     }
 
-    Ok(())
+    /// Reads the next block, and returns it with the number of lines which precede it. The last
+    /// block is the rest of the input. Returns `None` once the whole input has been returned.
+    pub(super) fn next_block(&mut self) -> ObjResult<Option<(usize, &[u8])>> {
+        let last = &self.buffer[..self.len];
+        self.line += last.iter().filter(|&&b| b == b'\n').count();
+        self.buffer.drain(..self.len);
+
+        self.len = loop {
+            if self.buffer.len() >= BLOCK_LEN
+                && let Some(end) = last_line_end(&self.buffer)
+            {
+                break end;
+            }
+            if self.finished {
+                break self.buffer.len();
+            }
+
+            let chunk = self.input.fill_buf()?;
+            if chunk.is_empty() {
+                self.finished = true;
+                continue;
+            }
+            let len = chunk.len();
+            self.buffer.extend_from_slice(chunk);
+            self.input.consume(len);
+        };
+
+        if self.len == 0 {
+            return Ok(None);
+        }
+        Ok(Some((self.line, &self.buffer[..self.len])))
+    }
+}
+
+/// Whether a physical line, with its line terminator stripped, continues on the next line.
+pub(super) fn is_continued(line: &[u8]) -> bool {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = &line[..comment_start(line).unwrap_or(line.len())];
+    line.ends_with(b"\\")
+}
+
+/// Returns the index after the last newline of `input` which ends a logical line, or `None` if
+/// no logical line ends in `input`.
+pub(super) fn last_line_end(input: &[u8]) -> Option<usize> {
+    let mut end = input.len();
+    loop {
+        let newline = input[..end].iter().rposition(|&b| b == b'\n')?;
+        let start = input[..newline]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |idx| idx + 1);
+        if !is_continued(&input[start..newline]) {
+            return Some(newline + 1);
+        }
+        end = newline;
+    }
+}
+
+/// Returns the text which follows the statement of a logical line, trimmed.
+fn arguments(buffer: &str) -> &str {
+    buffer
+        .trim_start()
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, text)| text.trim())
 }
 
 #[test]
@@ -119,23 +276,75 @@ bmat u  1       -3      3       -1      0       3       -6      3       \
 bmat u  1       -3      3       -1      0       3       -6      3       0       0       3       -3      0       0       0       1
 "#;
 
+    let mut bmat_lines = Vec::new();
     assert!(
-        lex(&mut input.as_bytes(), |stmt, args| {
+        lex(input.as_bytes(), |line, stmt, args, text| {
             match stmt {
-                "statement0" => assert_eq!(args, ["arg0", "arg1", "arg2"]),
-                "statement1" => assert_eq!(args, ["arg0", "arg1"]),
-                "statement2" => assert_eq!(args, ["Hello,", "world!"]),
-                "bmat" => assert_eq!(
-                    args,
-                    [
-                        "u", "1", "-3", "3", "-1", "0", "3", "-6", "3", "0", "0", "3", "-3", "0",
-                        "0", "0", "1"
-                    ]
-                ),
+                "statement0" => {
+                    assert_eq!(line, 2);
+                    assert_eq!(args, ["arg0", "arg1", "arg2"])
+                }
+                "statement1" => {
+                    assert_eq!(line, 3);
+                    assert_eq!(args, ["arg0", "arg1"]);
+                    assert_eq!(text, "arg0    arg1")
+                }
+                "statement2" => {
+                    assert_eq!(line, 5);
+                    assert_eq!(args, ["Hello,", "world!"]);
+                    assert_eq!(text, "Hello, world!")
+                }
+                "bmat" => {
+                    bmat_lines.push(line);
+                    assert_eq!(
+                        args,
+                        [
+                            "u", "1", "-3", "3", "-1", "0", "3", "-6", "3", "0", "0", "3", "-3",
+                            "0", "0", "0", "1"
+                        ]
+                    )
+                }
                 _ => panic!("Unit test failed"),
             }
             Ok(())
         })
         .is_ok()
     );
+
+    // Lines connected with backslashes report the number of their first line
+    assert_eq!(bmat_lines, [6, 10, 12]);
+}
+
+#[test]
+fn test_lex_bytes() {
+    // CRLF line endings, and comments which are not valid UTF-8
+    let input = b"v 1 2 3\r\nvn 0 0 1 # \xff\xfe\r\ng Left \\\r\n  Arm\r\nv 4 5 6";
+
+    let mut statements = Vec::new();
+    lex(input, |line, stmt, args, text| {
+        statements.push((line, stmt.to_string(), args.join(" "), text.to_string()));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        statements,
+        [
+            (1, "v".into(), "1 2 3".into(), "1 2 3".into()),
+            (2, "vn".into(), "0 0 1".into(), "0 0 1".into()),
+            (3, "g".into(), "Left Arm".into(), "Left    Arm".into()),
+            (5, "v".into(), "4 5 6".into(), "4 5 6".into()),
+        ]
+    );
+
+    assert!(lex(b"v 1 2 \xff", |_, _, _, _| Ok(())).is_err());
+    assert!(lex(b"v 1 2 \\", |_, _, _, _| Ok(())).is_err());
+}
+
+#[test]
+fn test_last_line_end() {
+    assert_eq!(last_line_end(b"v 1 2 3"), None);
+    assert_eq!(last_line_end(b"v 1 2 3\nv 4"), Some(8));
+    assert_eq!(last_line_end(b"v 1 2 3\r\nf 1 \\\r\n2 3"), Some(9));
+    assert_eq!(last_line_end(b"f 1 \\\n2 3\n"), Some(10));
+    assert_eq!(last_line_end(b"f 1 \\# comment\n2 3"), None);
 }
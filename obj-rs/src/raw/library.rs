@@ -0,0 +1,142 @@
+//! Loads the `.mtl` files which a `.obj` file refers to with `mtllib`
+
+use crate::error::ObjResult;
+use crate::raw::material::{Material, MtlColor};
+use crate::raw::resolver::{FileSystem, Resolver};
+use crate::raw::{
+    ParseOptions, RawObj, UnrecognizedStatement, parse_mtl_bytes_with_options, parse_rfl_bytes,
+};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::{Path, PathBuf};
+
+/// Materials of a `.obj` file, loaded from every `.mtl` file of its `mtllib` statements.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MaterialLibrary {
+    /// Map from the material name to its properties. If several files define the same material,
+    /// the one which is listed first wins, as the format specifies.
+    pub materials: HashMap<String, Material>,
+    /// Map from the material name to the path of the `.mtl` file which defines it.
+    pub sources: HashMap<String, PathBuf>,
+    /// Materials which are used by `usemtl` but defined by none of the files, sorted.
+    pub missing_materials: Vec<String>,
+    /// Statements which have been ignored according to `ParseOptions`, with the path of their
+    /// `.mtl` file.
+    pub unrecognized_statements: Vec<(PathBuf, UnrecognizedStatement)>,
+}
+
+impl MaterialLibrary {
+    /// Resolves a path which a material refers to, such as a texture map, against the directory
+    /// of the `.mtl` file which defines the material.
+    pub fn resolve_path(&self, material: &str, path: impl AsRef<Path>) -> PathBuf {
+        match self
+            .sources
+            .get(material)
+            .and_then(|source| source.parent())
+        {
+            Some(directory) => directory.join(path),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Reads a file which a material refers to, such as the `file` of a `MtlTextureMap` or the
+    /// `.rfl` file of a `MtlColor::Spectral`, with `resolver`.
+    pub fn read<R: Resolver + ?Sized>(
+        &self,
+        material: &str,
+        path: impl AsRef<Path>,
+        resolver: &mut R,
+    ) -> ObjResult<Vec<u8>> {
+        Ok(resolver.read(&self.resolve_path(material, path))?)
+    }
+
+    /// Converts a color of a material into linear sRGB. The `.rfl` file of a
+    /// `MtlColor::Spectral` is read with `resolver`.
+    pub fn to_rgb<R: Resolver + ?Sized>(
+        &self,
+        material: &str,
+        color: &MtlColor,
+        resolver: &mut R,
+    ) -> ObjResult<[f32; 3]> {
+        let curve = match color {
+            MtlColor::Spectral(file, _) => {
+                Some(parse_rfl_bytes(&self.read(material, file, resolver)?)?)
+            }
+            _ => None,
+        };
+        // Never `None`, since the curve of a spectral color is given
+        Ok(color.to_rgb(curve.as_ref()).unwrap_or_default())
+    }
+
+    /// Paths of every texture map and `.rfl` file which the materials refer to, resolved, sorted
+    /// and deduplicated.
+    pub fn referenced_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for (name, material) in &self.materials {
+            let maps = material
+                .texture_maps()
+                .into_iter()
+                .filter_map(|(_, map)| map.as_ref());
+            for map in maps.chain(&material.reflection_maps) {
+                files.push(self.resolve_path(name, &map.file));
+            }
+            for (_, color) in material.colors() {
+                if let Some(MtlColor::Spectral(file, _)) = color {
+                    files.push(self.resolve_path(name, file));
+                }
+            }
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+/// Loads the `.mtl` files of `obj` from the file system. Their paths are resolved against
+/// `RawObj::directory`.
+pub fn load_materials(obj: &RawObj) -> ObjResult<MaterialLibrary> {
+    load_materials_with(obj, &ParseOptions::default(), &mut FileSystem)
+}
+
+/// Loads the `.mtl` files of `obj` with the given options, reading them with `resolver`.
+///
+/// `resolver` receives the paths of `mtllib` statements resolved against `RawObj::directory`.
+/// This allows loading them from archives or virtual file systems.
+pub fn load_materials_with<R: Resolver + ?Sized>(
+    obj: &RawObj,
+    options: &ParseOptions,
+    resolver: &mut R,
+) -> ObjResult<MaterialLibrary> {
+    let mut library = MaterialLibrary::default();
+
+    for file in &obj.material_libraries {
+        let path = obj.resolve_path(file);
+        let mtl = parse_mtl_bytes_with_options(&resolver.read(&path)?, options)?;
+
+        for (name, material) in mtl.materials {
+            if let Entry::Vacant(entry) = library.materials.entry(name) {
+                library.sources.insert(entry.key().clone(), path.clone());
+                entry.insert(material);
+            }
+        }
+        library.unrecognized_statements.extend(
+            mtl.unrecognized_statements
+                .into_iter()
+                .map(|statement| (path.clone(), statement)),
+        );
+    }
+
+    // Polygons without `usemtl` belong to the mesh of an empty name. Materials which no element
+    // follows are missing from the meshes, but not from `material_names`.
+    library.missing_materials = obj
+        .meshes
+        .keys()
+        .chain(&obj.material_names)
+        .filter(|name| !name.is_empty() && !library.materials.contains_key(*name))
+        .cloned()
+        .collect();
+    library.missing_materials.sort();
+    library.missing_materials.dedup();
+
+    Ok(library)
+}
@@ -1,23 +1,50 @@
 //! Parses `.mtl` format which stores material data
 
 use crate::error::{ObjResult, make_error};
-use crate::raw::lexer::lex;
-use crate::raw::util::parse_args;
+use crate::raw::lexer::{Callback, lex, lex_read};
+use crate::raw::util::{parse_arg, parse_args};
+use crate::raw::{ParseOptions, Unrecognized, UnrecognizedStatement};
 use std::collections::HashMap;
 use std::fmt;
 use std::io::BufRead;
 use std::mem::take;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 /// Parses a wavefront `.mtl` format *(incomplete)*
 pub fn parse_mtl<T: BufRead>(input: T) -> ObjResult<RawMtl> {
+    parse_mtl_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.mtl` format *(incomplete)*, with the given options.
+pub fn parse_mtl_with_options<T: BufRead>(input: T, options: &ParseOptions) -> ObjResult<RawMtl> {
+    parse_mtl_lexed(|callback| lex_read(input, callback), options)
+}
+
+/// Parses a wavefront `.mtl` format *(incomplete)* from a buffer in memory.
+pub fn parse_mtl_bytes(input: &[u8]) -> ObjResult<RawMtl> {
+    parse_mtl_bytes_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.mtl` format *(incomplete)* from a buffer in memory, with the given
+/// options.
+pub fn parse_mtl_bytes_with_options(input: &[u8], options: &ParseOptions) -> ObjResult<RawMtl> {
+    parse_mtl_lexed(|callback| lex(input, callback), options)
+}
+
+/// Parses the statements which `lex` calls back with.
+fn parse_mtl_lexed<L>(lex: L, options: &ParseOptions) -> ObjResult<RawMtl>
+where
+    L: FnOnce(&mut Callback) -> ObjResult<()>,
+{
     let mut materials = HashMap::new();
+    let mut unrecognized = Unrecognized::new(options);
 
     // Properties of the material being currently parsed
     let mut name: Option<String> = None;
     let mut mat: Material = Material::default();
 
-    lex(input, |stmt, args| {
+    lex(&mut |line, stmt, args, text| {
         match stmt {
             // Material name statement
             "newmtl" => {
@@ -37,45 +64,86 @@ pub fn parse_mtl<T: BufRead>(input: T) -> ObjResult<RawMtl> {
             "Kd" => mat.diffuse = Some(parse_color(args)?),
             "Ks" => mat.specular = Some(parse_color(args)?),
             "Ke" => mat.emissive = Some(parse_color(args)?),
-            "Km" => unimplemented!(),
+            "Km" => mat.mirror_color = Some(parse_color(args)?),
             "Tf" => mat.transmission_filter = Some(parse_color(args)?),
             "Ns" => match args {
-                [arg] => mat.specular_exponent = Some(arg.parse()?),
+                [arg] => mat.specular_exponent = Some(parse_arg(arg)?),
                 _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
             },
             "Ni" => match args {
-                [arg] => mat.optical_density = Some(arg.parse()?),
+                [arg] => mat.optical_density = Some(parse_arg(arg)?),
                 _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
             },
             "illum" => match args {
-                [arg] => mat.illumination_model = Some(arg.parse()?),
+                [arg] => mat.illumination_model = Some(parse_arg(arg)?),
                 _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
             },
             "d" => match args {
-                [arg] => mat.dissolve = Some(arg.parse()?),
+                [arg] => mat.dissolve = Some(parse_arg(arg)?),
                 _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
             },
             "Tr" => match args {
-                [arg] => mat.dissolve = Some(1.0 - arg.parse::<f32>()?),
+                [arg] => mat.dissolve = Some(1.0 - parse_arg::<f32>(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "sharpness" => match args {
+                [arg] => mat.sharpness = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+
+            // Physically based rendering statements
+            "Pr" => match args {
+                [arg] => mat.roughness = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "Pm" => match args {
+                [arg] => mat.metallic = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "Ps" => match args {
+                [arg] => mat.sheen = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "Pc" => match args {
+                [arg] => mat.clearcoat_thickness = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "Pcr" => match args {
+                [arg] => mat.clearcoat_roughness = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "aniso" => match args {
+                [arg] => mat.anisotropy = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "anisor" => match args {
+                [arg] => mat.anisotropy_rotation = Some(parse_arg(arg)?),
                 _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
             },
 
             // Texture map statements
-            "map_Ka" => mat.ambient_map = Some(parse_texture_map(args)?),
-            "map_Kd" => mat.diffuse_map = Some(parse_texture_map(args)?),
-            "map_Ks" => mat.specular_map = Some(parse_texture_map(args)?),
-            "map_Ke" => mat.emissive_map = Some(parse_texture_map(args)?),
-            "map_d" => mat.dissolve_map = Some(parse_texture_map(args)?),
-            "map_aat" => unimplemented!(),
-            "map_refl" => unimplemented!(),
-            "map_bump" | "map_Bump" | "bump" => mat.bump_map = Some(parse_texture_map(args)?),
-            "disp" => unimplemented!(),
-
-            // Reflection map statement
-            "refl" => unimplemented!(),
+            "map_Ka" => mat.ambient_map = Some(parse_texture_map(args, text)?),
+            "map_Kd" => mat.diffuse_map = Some(parse_texture_map(args, text)?),
+            "map_Ks" => mat.specular_map = Some(parse_texture_map(args, text)?),
+            "map_Ke" => mat.emissive_map = Some(parse_texture_map(args, text)?),
+            "map_d" => mat.dissolve_map = Some(parse_texture_map(args, text)?),
+            "map_aat" => match args {
+                [arg] => mat.antialias_textures = Some(parse_on_off(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "map_bump" | "map_Bump" | "bump" => mat.bump_map = Some(parse_texture_map(args, text)?),
+            "map_Pr" => mat.roughness_map = Some(parse_texture_map(args, text)?),
+            "map_Pm" => mat.metallic_map = Some(parse_texture_map(args, text)?),
+            "map_Ps" => mat.sheen_map = Some(parse_texture_map(args, text)?),
+            "norm" => mat.normal_map = Some(parse_texture_map(args, text)?),
+            "disp" => mat.displacement_map = Some(parse_texture_map(args, text)?),
+            "decal" => mat.decal_map = Some(parse_texture_map(args, text)?),
+
+            // Reflection map statement, repeated for each face of a cube map
+            "refl" | "map_refl" => mat.reflection_maps.push(parse_texture_map(args, text)?),
 
             // Unexpected statement
-            _ => make_error!(UnexpectedStatement, "Received unknown statement"),
+            _ => unrecognized.unknown(line, stmt, args)?,
         }
 
         Ok(())
@@ -86,7 +154,11 @@ pub fn parse_mtl<T: BufRead>(input: T) -> ObjResult<RawMtl> {
         materials.insert(name, mat);
     }
 
-    Ok(RawMtl { materials })
+    Ok(RawMtl {
+        materials,
+        unrecognized_statements: unrecognized.statements,
+        directory: None,
+    })
 }
 
 /// Parses a color from the arguments of a statement
@@ -104,7 +176,7 @@ fn parse_color(args: &[&str]) -> ObjResult<MtlColor> {
 
         "spectral" => match args[1..] {
             [name] => MtlColor::Spectral(name.to_string(), 1.0),
-            [name, multiplier] => MtlColor::Spectral(name.to_string(), multiplier.parse()?),
+            [name, multiplier] => MtlColor::Spectral(name.to_string(), parse_arg(multiplier)?),
             _ => make_error!(WrongNumberOfArguments, "Expected 1 or 2 arguments"),
         },
 
@@ -116,12 +188,19 @@ fn parse_color(args: &[&str]) -> ObjResult<MtlColor> {
     })
 }
 
-/// Parses a texture map specification from the arguments of a statement
-fn parse_texture_map(args: &[&str]) -> ObjResult<MtlTextureMap> {
-    if args.is_empty() {
-        make_error!(WrongNumberOfArguments, "Expected at least 1 argument");
+/// Parses an `on`/`off` argument. `true`/`false`, which some exporters write, are accepted too.
+fn parse_on_off(arg: &str) -> ObjResult<bool> {
+    if arg.eq_ignore_ascii_case("on") || arg.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if arg.eq_ignore_ascii_case("off") || arg.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        make_error!(WrongTypeOfArguments, "Expected `on` or `off`", arg)
     }
+}
 
+/// Parses a texture map specification from the arguments of a statement and their text
+fn parse_texture_map(args: &[&str], text: &str) -> ObjResult<MtlTextureMap> {
     let mut texture_map = MtlTextureMap::default();
 
     // Format:
@@ -129,220 +208,193 @@ fn parse_texture_map(args: &[&str]) -> ObjResult<MtlTextureMap> {
     //
     // See:
     //      https://paulbourke.net/dataformats/mtl
-
-    // Last argument is always the filename
-    if let Some(&filename) = args.last() {
-        texture_map.file = filename.to_string();
-    } else {
-        make_error!(WrongNumberOfArguments, "Expected at least a filename")
+    let options = parse_options_and_args(&mut texture_map, args)?;
+
+    // The rest of the text is the filename, which may contain spaces
+    let mut file = text;
+    for _ in 0..options {
+        file = file
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest);
     }
-
-    // Process all options (therefore all arguments except the last one)
-    let options_iter = args.iter().take(args.len() - 1).peekable();
-    parse_options_and_args(&mut texture_map, options_iter)?;
+    texture_map.file = match file.trim() {
+        "" => make_error!(WrongNumberOfArguments, "Expected a filename"),
+        file => file.to_string(),
+    };
 
     Ok(texture_map)
 }
 
-/// Parses options and their arguments and applies them to the texture map
-fn parse_options_and_args<'a, I>(
-    texture_map: &mut MtlTextureMap,
-    mut iter: std::iter::Peekable<I>,
-) -> ObjResult<()>
-where
-    I: Iterator<Item = &'a &'a str>,
-{
-    while let Some(&option) = iter.next() {
-        match option {
+/// Parses options and their arguments and applies them to the texture map. Returns the number of
+/// arguments which belong to the options, all of which precede the filename.
+fn parse_options_and_args(texture_map: &mut MtlTextureMap, args: &[&str]) -> ObjResult<usize> {
+    let mut index = 0;
+
+    // The last argument always belongs to the filename
+    while index + 1 < args.len() && args[index].starts_with('-') {
+        let option = args[index];
+        let values = &args[index + 1..args.len() - 1];
+
+        let count = match option {
             "-bm" => {
                 // Parse bump multiplier
-                if let Some(&value) = iter.next() {
-                    texture_map.bump_multiplier = value.parse::<f32>()?;
-                } else {
-                    make_error!(WrongNumberOfArguments, "Missing bump value for -bm option");
-                }
+                texture_map.bump_multiplier = parse_arg(option_value(option, values)?)?;
+                1
             }
 
-            "-o" => {
-                // Parse three coordinates (u,v,w) for texture origin offset
-                let u = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing u value for -o option")
-                    }
-                };
-
-                let v = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing v value for -o option")
-                    }
-                };
-
-                let w = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing w value for -o option")
-                    }
-                };
+            "-boost" => {
+                // Parse sharpness boost
+                texture_map.boost = parse_arg(option_value(option, values)?)?;
+                1
+            }
 
-                texture_map.origin_offset =
-                    [u.parse::<f32>()?, v.parse::<f32>()?, w.parse::<f32>()?];
+            "-o" => {
+                // Parse up to three coordinates (u,v,w) for texture origin offset
+                let (offset, count) = parse_uvw(option, values, [0.0; 3])?;
+                texture_map.origin_offset = offset;
+                count
             }
 
             "-s" => {
-                // Parse three coordinates (u,v,w) for texture scale
-                let u = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing u value for -s option")
-                    }
-                };
-
-                let v = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing v value for -s option")
-                    }
-                };
-
-                let w = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing w value for -s option")
-                    }
-                };
-
-                texture_map.scale = [u.parse::<f32>()?, v.parse::<f32>()?, w.parse::<f32>()?];
+                // Parse up to three coordinates (u,v,w) for texture scale
+                let (scale, count) = parse_uvw(option, values, [1.0; 3])?;
+                texture_map.scale = scale;
+                count
             }
 
             "-t" => {
-                // Parse three coordinates (u,v,w) for texture turbulence
-                let u = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing u value for -t option")
-                    }
-                };
-
-                let v = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing v value for -t option")
-                    }
-                };
-
-                let w = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing w value for -t option")
-                    }
-                };
-
-                texture_map.turbulence = [u.parse::<f32>()?, v.parse::<f32>()?, w.parse::<f32>()?];
+                // Parse up to three coordinates (u,v,w) for texture turbulence
+                let (turbulence, count) = parse_uvw(option, values, [0.0; 3])?;
+                texture_map.turbulence = turbulence;
+                count
             }
 
             "-texres" => {
                 // Parse texture resolution
-                if let Some(&value) = iter.next() {
-                    texture_map.resolution = value.parse::<u32>()?;
-                } else {
-                    make_error!(
-                        WrongNumberOfArguments,
-                        "Missing resolution value for -texres option"
-                    );
-                }
+                texture_map.resolution = parse_arg(option_value(option, values)?)?;
+                1
             }
 
             "-clamp" => {
                 // Set clamping flag
-                let value = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(
-                            WrongNumberOfArguments,
-                            "Missing on/off value for -clamp option"
-                        )
-                    }
-                }
-                .to_lowercase();
+                texture_map.clamping = parse_on_off(option_value(option, values)?)?;
+                1
+            }
 
-                texture_map.clamping = value == "on" || value == "true";
+            "-cc" => {
+                // Set color correction flag
+                texture_map.color_correction = parse_on_off(option_value(option, values)?)?;
+                1
             }
 
             "-mm" => {
                 // Parse two values for base and gain
-                let base = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing base value for -mm option")
+                match values {
+                    [base, gain, ..] => {
+                        texture_map.base_gain = [parse_arg(base)?, parse_arg(gain)?];
                     }
-                };
-
-                let gain = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(WrongNumberOfArguments, "Missing gain value for -mm option")
-                    }
-                };
-
-                texture_map.base_gain = [base.parse::<f32>()?, gain.parse::<f32>()?];
+                    _ => make_error!(WrongNumberOfArguments, "Missing values for -mm option"),
+                }
+                2
             }
 
             "-blendu" => {
                 // Set horizontal texture blending flag
-                let value = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(
-                            WrongNumberOfArguments,
-                            "Missing on/off value for -blendu option"
-                        )
-                    }
-                }
-                .to_lowercase();
-
-                texture_map.blend_u = value == "on" || value == "true";
+                texture_map.blend_u = parse_on_off(option_value(option, values)?)?;
+                1
             }
 
             "-blendv" => {
                 // Set vertical texture blending flag
-                let value = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(
-                            WrongNumberOfArguments,
-                            "Missing on/off value for -blendv option"
-                        )
-                    }
-                }
-                .to_lowercase();
+                texture_map.blend_v = parse_on_off(option_value(option, values)?)?;
+                1
+            }
 
-                texture_map.blend_v = value == "on" || value == "true";
+            "-type" => {
+                // Set reflection map type
+                let value = option_value(option, values)?;
+                texture_map.reflection_type = match MtlReflectionType::from_str(value) {
+                    Ok(reflection_type) => Some(reflection_type),
+                    Err(message) => make_error!(WrongTypeOfArguments, message, value),
+                };
+                1
             }
 
             "-imfchan" => {
                 // Set image file channel
-                let value = match iter.next() {
-                    Some(&val) => val,
-                    None => {
-                        make_error!(
-                            WrongNumberOfArguments,
-                            "Missing channel value for -imfchan option"
-                        )
-                    }
-                }
-                .to_lowercase();
+                let value = option_value(option, values)?;
+                texture_map.channel = match MtlTextureChannel::from_str(&value.to_lowercase()) {
+                    Ok(channel) => channel,
+                    Err(message) => make_error!(WrongTypeOfArguments, message, value),
+                };
+                1
+            }
 
-                texture_map.channel = MtlTextureChannel::from_str(&value).unwrap();
+            // Preserve unknown options, along with the values which follow them
+            _ => {
+                let count = values
+                    .iter()
+                    .take_while(|value| is_option_value(value))
+                    .count();
+                texture_map.unknown_options.push((
+                    option.to_string(),
+                    values[..count]
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect(),
+                ));
+                count
             }
+        };
 
-            // Skip unknown options
-            _ => {}
+        index += 1 + count;
+    }
+
+    Ok(index)
+}
+
+/// Returns the single value of an option.
+fn option_value<'a>(option: &str, values: &[&'a str]) -> ObjResult<&'a str> {
+    match values.first() {
+        Some(value) => Ok(value),
+        None => make_error!(
+            WrongNumberOfArguments,
+            format!("Missing value for {} option", option)
+        ),
+    }
+}
+
+/// Parses the u, v and w values of an option, of which v and w are optional. The missing ones
+/// are taken from `default`. Returns the values with the number of arguments they took.
+fn parse_uvw(option: &str, values: &[&str], default: [f32; 3]) -> ObjResult<([f32; 3], usize)> {
+    let mut uvw = default;
+    let mut count = 0;
+    for value in values.iter().take(3) {
+        match parse_arg::<f32>(value) {
+            Ok(value) => uvw[count] = value,
+            Err(_) if count > 0 => break,
+            Err(e) => return Err(e),
         }
+        count += 1;
     }
 
-    Ok(())
+    if count == 0 {
+        make_error!(
+            WrongNumberOfArguments,
+            format!("Missing u value for {} option", option)
+        );
+    }
+    Ok((uvw, count))
+}
+
+/// Whether an argument following an unknown option is one of its values, rather than the next
+/// option or the filename.
+fn is_option_value(arg: &str) -> bool {
+    parse_arg::<f32>(arg).is_ok()
+        || ["on", "off", "true", "false"]
+            .iter()
+            .any(|value| arg.eq_ignore_ascii_case(value))
 }
 
 /// Low-level Rust binding for `.mtl` format *(incomplete)*.
@@ -350,6 +402,21 @@ where
 pub struct RawMtl {
     /// Map from the material name to its properties
     pub materials: HashMap<String, Material>,
+    /// Statements which have been ignored according to `ParseOptions`.
+    pub unrecognized_statements: Vec<UnrecognizedStatement>,
+    /// Directory of the `.mtl` file, if it has been parsed by `parse_mtl_file`.
+    pub directory: Option<PathBuf>,
+}
+
+impl RawMtl {
+    /// Resolves a path which the `.mtl` file refers to, such as a texture map, against
+    /// `directory`.
+    pub fn resolve_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        match &self.directory {
+            Some(directory) => directory.join(path),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
 }
 
 /// A single material from a `.mtl` file
@@ -385,6 +452,76 @@ pub struct Material {
     pub dissolve_map: Option<MtlTextureMap>,
     /// The bump map (normal map), specified by `bump`
     pub bump_map: Option<MtlTextureMap>,
+    /// The roughness of the PBR extension, specified by `Pr`
+    pub roughness: Option<f32>,
+    /// The metallic factor of the PBR extension, specified by `Pm`
+    pub metallic: Option<f32>,
+    /// The sheen of the PBR extension, specified by `Ps`
+    pub sheen: Option<f32>,
+    /// The clearcoat thickness of the PBR extension, specified by `Pc`
+    pub clearcoat_thickness: Option<f32>,
+    /// The clearcoat roughness of the PBR extension, specified by `Pcr`
+    pub clearcoat_roughness: Option<f32>,
+    /// The anisotropy of the PBR extension, specified by `aniso`
+    pub anisotropy: Option<f32>,
+    /// The anisotropy rotation of the PBR extension, specified by `anisor`
+    pub anisotropy_rotation: Option<f32>,
+    /// The roughness map of the PBR extension, specified by `map_Pr`
+    pub roughness_map: Option<MtlTextureMap>,
+    /// The metallic map of the PBR extension, specified by `map_Pm`
+    pub metallic_map: Option<MtlTextureMap>,
+    /// The sheen map of the PBR extension, specified by `map_Ps`
+    pub sheen_map: Option<MtlTextureMap>,
+    /// The normal map of the PBR extension, specified by `norm`
+    pub normal_map: Option<MtlTextureMap>,
+    /// The mirror color, specified by `Km`. The format does not define `Km`, but some exporters
+    /// write it as a single value, which results in a grey `MtlColor::Rgb`.
+    pub mirror_color: Option<MtlColor>,
+    /// The sharpness of reflections, specified by `sharpness`
+    pub sharpness: Option<f32>,
+    /// Whether texture maps are antialiased, specified by `map_aat`
+    pub antialias_textures: Option<bool>,
+    /// The displacement map, specified by `disp`
+    pub displacement_map: Option<MtlTextureMap>,
+    /// The decal map, specified by `decal`
+    pub decal_map: Option<MtlTextureMap>,
+    /// The reflection maps, specified by `refl`. A cube map has one for each face, told apart by
+    /// `MtlTextureMap::reflection_type`.
+    pub reflection_maps: Vec<MtlTextureMap>,
+}
+
+impl Material {
+    /// Colors of the material, with the statements which specify them.
+    pub(crate) fn colors(&self) -> [(&'static str, &Option<MtlColor>); 6] {
+        [
+            ("Ka", &self.ambient),
+            ("Kd", &self.diffuse),
+            ("Ks", &self.specular),
+            ("Ke", &self.emissive),
+            ("Km", &self.mirror_color),
+            ("Tf", &self.transmission_filter),
+        ]
+    }
+
+    /// Texture maps of the material, with the statements which specify them.
+    ///
+    /// Reflection maps are not included, as there may be several of them.
+    pub(crate) fn texture_maps(&self) -> [(&'static str, &Option<MtlTextureMap>); 12] {
+        [
+            ("map_Ka", &self.ambient_map),
+            ("map_Kd", &self.diffuse_map),
+            ("map_Ks", &self.specular_map),
+            ("map_Ke", &self.emissive_map),
+            ("map_d", &self.dissolve_map),
+            ("bump", &self.bump_map),
+            ("map_Pr", &self.roughness_map),
+            ("map_Pm", &self.metallic_map),
+            ("map_Ps", &self.sheen_map),
+            ("norm", &self.normal_map),
+            ("disp", &self.displacement_map),
+            ("decal", &self.decal_map),
+        ]
+    }
 }
 
 /// A color specified in a `.mtl` file
@@ -448,6 +585,57 @@ impl fmt::Display for MtlTextureChannel {
     }
 }
 
+/// The type of a reflection map, specified via -type option in an `.mtl` file
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MtlReflectionType {
+    /// A single spherical map
+    Sphere,
+    /// Top face of a cube map
+    CubeTop,
+    /// Bottom face of a cube map
+    CubeBottom,
+    /// Front face of a cube map
+    CubeFront,
+    /// Back face of a cube map
+    CubeBack,
+    /// Left face of a cube map
+    CubeLeft,
+    /// Right face of a cube map
+    CubeRight,
+}
+
+impl FromStr for MtlReflectionType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sphere" => Ok(MtlReflectionType::Sphere),
+            "cube_top" => Ok(MtlReflectionType::CubeTop),
+            "cube_bottom" => Ok(MtlReflectionType::CubeBottom),
+            "cube_front" => Ok(MtlReflectionType::CubeFront),
+            "cube_back" => Ok(MtlReflectionType::CubeBack),
+            "cube_left" => Ok(MtlReflectionType::CubeLeft),
+            "cube_right" => Ok(MtlReflectionType::CubeRight),
+            _ => Err(format!("Invalid reflection type: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for MtlReflectionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            MtlReflectionType::Sphere => "sphere",
+            MtlReflectionType::CubeTop => "cube_top",
+            MtlReflectionType::CubeBottom => "cube_bottom",
+            MtlReflectionType::CubeFront => "cube_front",
+            MtlReflectionType::CubeBack => "cube_back",
+            MtlReflectionType::CubeLeft => "cube_left",
+            MtlReflectionType::CubeRight => "cube_right",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// A texture map specified in a `.mtl` file
 #[derive(Clone, PartialEq, Debug)]
 pub struct MtlTextureMap {
@@ -483,6 +671,18 @@ pub struct MtlTextureMap {
 
     /// Texture channel
     pub channel: MtlTextureChannel,
+
+    /// Type of a reflection map, `None` for other texture maps
+    pub reflection_type: Option<MtlReflectionType>,
+
+    /// Sharpness boost (increases the sharpness of mip-mapped textures)
+    pub boost: f32,
+
+    /// Color correction flag (only applies to `map_Ka`, `map_Kd` and `map_Ks`)
+    pub color_correction: bool,
+
+    /// Options which are not defined by the format, with their values
+    pub unknown_options: Vec<(String, Vec<String>)>,
 }
 
 impl Default for MtlTextureMap {
@@ -499,6 +699,10 @@ impl Default for MtlTextureMap {
             blend_u: false,
             blend_v: false,
             channel: MtlTextureChannel::Red,
+            reflection_type: None,
+            boost: 0.0,
+            color_correction: false,
+            unknown_options: Vec::new(),
         }
     }
 }
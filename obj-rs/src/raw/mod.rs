@@ -1,9 +1,117 @@
 //! Provides low-level API for Wavefront OBJ format.
 
+mod file;
+pub mod freeform;
 mod lexer;
+mod library;
 pub mod material;
 pub mod object;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pbr;
+mod resolver;
+mod spectral;
+pub mod stream;
 mod util;
+mod writer;
 
-pub use self::material::{RawMtl, parse_mtl};
-pub use self::object::{RawObj, parse_obj};
+pub use self::file::{
+    parse_mtl_file, parse_mtl_file_with_options, parse_obj_file, parse_obj_file_with_options,
+};
+#[cfg(feature = "memmap2")]
+pub use self::file::{parse_obj_file_mapped, parse_obj_file_mapped_with_options};
+pub use self::library::{MaterialLibrary, load_materials, load_materials_with};
+pub use self::material::{
+    RawMtl, parse_mtl, parse_mtl_bytes, parse_mtl_bytes_with_options, parse_mtl_with_options,
+};
+pub use self::object::{
+    RawObj, parse_obj, parse_obj_bytes, parse_obj_bytes_with_options, parse_obj_with_options,
+};
+#[cfg(feature = "rayon")]
+pub use self::parallel::{parse_obj_parallel, parse_obj_parallel_with_options};
+pub use self::pbr::{AlphaMode, PbrMaterial};
+pub use self::resolver::{FileSystem, Resolver};
+pub use self::spectral::{SpectralCurve, parse_rfl, parse_rfl_bytes};
+pub use self::stream::{ObjEvent, ObjEvents, parse_obj_events, parse_obj_events_with_options};
+pub use self::writer::{write_material, write_mtl, write_obj};
+
+use crate::error::{ObjResult, make_error};
+
+/// Options for parsing `.obj` and `.mtl` formats.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ParseOptions {
+    /// Policy for statements which are defined by the format but not supported by obj-rs yet.
+    pub unsupported: StatementPolicy,
+    /// Policy for statements which are not defined by the format, such as vendor extensions.
+    pub unknown: StatementPolicy,
+}
+
+/// What to do when a statement cannot be parsed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum StatementPolicy {
+    /// Stop parsing and return an error.
+    #[default]
+    Error,
+    /// Ignore the statement.
+    Skip,
+    /// Ignore the statement, but record it in `unrecognized_statements`.
+    Collect,
+}
+
+/// A statement which has been ignored while parsing.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct UnrecognizedStatement {
+    /// The 1-based line number where the statement starts.
+    pub line: usize,
+    /// Keyword of the statement.
+    pub statement: String,
+    /// Arguments of the statement.
+    pub arguments: Vec<String>,
+}
+
+/// Applies `ParseOptions` to the statements which cannot be parsed.
+struct Unrecognized<'a> {
+    options: &'a ParseOptions,
+    statements: Vec<UnrecognizedStatement>,
+}
+
+impl<'a> Unrecognized<'a> {
+    fn new(options: &'a ParseOptions) -> Self {
+        Unrecognized {
+            options,
+            statements: Vec::new(),
+        }
+    }
+
+    /// Handles a statement which is defined by the format but not supported yet.
+    fn unsupported(&mut self, line: usize, stmt: &str, args: &[&str]) -> ObjResult<()> {
+        match self.options.unsupported {
+            StatementPolicy::Error => {
+                make_error!(UnsupportedStatement, "Received unsupported statement")
+            }
+            policy => self.ignore(policy, line, stmt, args),
+        }
+        Ok(())
+    }
+
+    /// Handles a statement which is not defined by the format.
+    fn unknown(&mut self, line: usize, stmt: &str, args: &[&str]) -> ObjResult<()> {
+        match self.options.unknown {
+            StatementPolicy::Error => {
+                make_error!(UnexpectedStatement, "Received unknown statement")
+            }
+            policy => self.ignore(policy, line, stmt, args),
+        }
+        Ok(())
+    }
+
+    fn ignore(&mut self, policy: StatementPolicy, line: usize, stmt: &str, args: &[&str]) {
+        if policy == StatementPolicy::Collect {
+            self.statements.push(UnrecognizedStatement {
+                line,
+                statement: stmt.to_string(),
+                arguments: args.iter().map(|arg| arg.to_string()).collect(),
+            });
+        }
+    }
+}
@@ -1,14 +1,17 @@
 //! Parses `.obj` format which stores 3D mesh data
 
-use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::io::BufRead;
 use std::mem;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 
 use crate::error::{ObjResult, make_error};
-use crate::raw::lexer::lex;
-use crate::raw::util::parse_args;
+use crate::raw::lexer::{Callback, lex, lex_read};
+use crate::raw::util::{parse_arg, parse_args, parse_floats};
+use crate::raw::{ParseOptions, Unrecognized, UnrecognizedStatement};
 
 macro_rules! parse_args {
     {
@@ -34,7 +37,7 @@ macro_rules! parse_args {
 
 // Helper function for handling the indexes.
 //
-// If total size of the collection is 5:
+// If total size of the collection, `len`, is 5:
 //
 // - ["1", "2", "3", "4", "5"] → [0, 1, 2, 3, 4]
 // - ["-5", "-4", "-3", "-2", "-1"] → [0, 1, 2, 3, 4]
@@ -47,10 +50,10 @@ macro_rules! parse_args {
 //
 // If the index is > 0 then it's simply the position in the list such
 // that 1 is the first vertex.
-fn try_index<T>(collection: &[T], input: &str) -> ObjResult<usize> {
+pub(super) fn try_index(len: usize, input: &str) -> ObjResult<usize> {
     use crate::error::{LoadError, LoadErrorKind, ObjError};
 
-    let len: isize = collection.len().try_into().map_err(|_| {
+    let len: isize = len.try_into().map_err(|_| {
         ObjError::Load(LoadError::new_internal(
             LoadErrorKind::IndexOutOfRange,
             "Too many items in collection".to_string(),
@@ -58,32 +61,61 @@ fn try_index<T>(collection: &[T], input: &str) -> ObjResult<usize> {
     })?;
 
     // Should be [-len, -1] ∪ [1, len]
-    let index: isize = input.parse()?;
+    let index: isize = parse_arg(input)?;
 
     let ret = if index < -len {
         // (∞, -len)
-        make_error!(IndexOutOfRange, "Too small index value");
+        make_error!(IndexOutOfRange, "Too small index value", input);
     } else if index < 0 {
         // [-len, 0)
         len + index
     } else if index == 0 {
         // {0}
-        make_error!(IndexOutOfRange, "Index value shouldn't be zero");
+        make_error!(IndexOutOfRange, "Index value shouldn't be zero", input);
     } else if index <= len {
         // (0, len]
         index - 1
     } else {
         // (len, ∞)
-        make_error!(IndexOutOfRange, "Too big index value");
+        make_error!(IndexOutOfRange, "Too big index value", input);
     };
 
     Ok(ret as usize)
 }
 
 /// Parses a wavefront `.obj` format.
+///
+/// The input is read and lexed in blocks of about 64 KiB, so it is never kept in memory as a
+/// whole. Use `parse_obj_events` to parse large files without storing their elements either.
 pub fn parse_obj<T: BufRead>(input: T) -> ObjResult<RawObj> {
+    parse_obj_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.obj` format, with the given options.
+pub fn parse_obj_with_options<T: BufRead>(input: T, options: &ParseOptions) -> ObjResult<RawObj> {
+    parse_obj_lexed(|callback| lex_read(input, callback), options)
+}
+
+/// Parses a wavefront `.obj` format from a buffer in memory, such as a memory-mapped file.
+///
+/// Unlike `parse_obj`, the input is never copied while it is being parsed.
+pub fn parse_obj_bytes(input: &[u8]) -> ObjResult<RawObj> {
+    parse_obj_bytes_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.obj` format from a buffer in memory, with the given options.
+pub fn parse_obj_bytes_with_options(input: &[u8], options: &ParseOptions) -> ObjResult<RawObj> {
+    parse_obj_lexed(|callback| lex(input, callback), options)
+}
+
+/// Parses the statements which `lex` calls back with.
+fn parse_obj_lexed<L>(lex: L, options: &ParseOptions) -> ObjResult<RawObj>
+where
+    L: FnOnce(&mut Callback) -> ObjResult<()>,
+{
     let mut name = None;
     let mut material_libraries = Vec::new();
+    let mut material_names = HashSet::new();
 
     let mut positions = Vec::new();
     let mut tex_coords = Vec::new();
@@ -93,173 +125,367 @@ pub fn parse_obj<T: BufRead>(input: T) -> ObjResult<RawObj> {
     let mut points = Vec::new();
     let mut lines = Vec::new();
     let mut polygons = Vec::new();
+    let mut curves = Vec::new();
+    let mut curves2 = Vec::new();
+    let mut surfaces = Vec::new();
+    let mut connections = Vec::new();
 
-    let counter = Counter::new(&points, &lines, &polygons);
+    // Free-form attributes which will be applied to the following curves and surfaces
+    let mut curve_type = None;
+    let mut degree = None;
+    let mut basis_matrix = (Vec::new(), Vec::new());
+    let mut step = (1, 1);
+    let mut curve_technique = None;
+    let mut surface_technique = None;
+
+    // Free-form element whose body is being currently parsed
+    let mut body = None;
+
+    let mut unrecognized = Unrecognized::new(options);
+
+    let counter = Counter::new(&points, &lines, &polygons, &curves, &surfaces);
+    let mut object_builder = GroupBuilder::new(&counter);
     let mut group_builder = GroupBuilder::with_default(&counter, String::from("default"));
     let mut mesh_builder = GroupBuilder::with_default(&counter, String::new());
     let mut smoothing_builder = GroupBuilder::new(&counter);
     let mut merging_builder = GroupBuilder::new(&counter);
 
-    lex(input, |stmt, args: &[&str]| {
+    lex(&mut |line, stmt, args: &[&str], text| {
         match stmt {
             // Vertex data
-            "v" => positions.push(match parse_args(args)?[..] {
-                [x, y, z, w] => (x, y, z, w),
-                [x, y, z] => (x, y, z, 1.0),
-                _ => make_error!(WrongNumberOfArguments, "Expected 3 or 4 arguments"),
-            }),
-            "vt" => tex_coords.push(match parse_args(args)?[..] {
-                [u, v, w] => (u, v, w),
-                [u, v] => (u, v, 0.0),
-                [u] => (u, 0.0, 0.0),
-                _ => make_error!(WrongNumberOfArguments, "Expected 1, 2 or 3 arguments"),
-            }),
-            "vn" => normals.push(match parse_args(args)?[..] {
-                [x, y, z] => (x, y, z),
-                _ => make_error!(WrongNumberOfArguments, "Expected 3 arguments"),
-            }),
-            "vp" => param_vertices.push(match parse_args(args)?[..] {
-                [u, v, w] => (u, v, w),
-                [u, v] => (u, v, 1.0),
-                [u] => (u, 0.0, 1.0),
-                _ => make_error!(WrongNumberOfArguments, "Expected 1, 2 or 3 arguments"),
-            }),
+            "v" => positions.push(parse_position(args)?),
+            "vt" => tex_coords.push(parse_tex_coord(args)?),
+            "vn" => normals.push(parse_normal(args)?),
+            "vp" => param_vertices.push(parse_param_vertex(args)?),
 
             // Free-form curve / surface attributes
-            // TODO: Use rational information
             "cstype" => {
-                let geometry = match args {
-                    ["rat", ty] => *ty,
-                    [ty] => *ty,
+                let (rational, ty) = match args {
+                    ["rat", ty] => (true, *ty),
+                    [ty] => (false, *ty),
                     _ => make_error!(WrongTypeOfArguments, "Expected 'rat xxx' or 'xxx' format"),
                 };
 
-                match geometry {
-                    "bmatrix" => unimplemented!(),
-                    "bezier" => unimplemented!(),
-                    "bspline" => unimplemented!(),
-                    "cardinal" => unimplemented!(),
-                    "taylor" => unimplemented!(),
+                let ty = match ty {
+                    "bmatrix" => CurveType::BasisMatrix,
+                    "bezier" => CurveType::Bezier,
+                    "bspline" => CurveType::BSpline,
+                    "cardinal" => CurveType::Cardinal,
+                    "taylor" => CurveType::Taylor,
                     _ => make_error!(
                         WrongTypeOfArguments,
                         "Expected one of 'bmatrix', 'bezier', 'bspline', 'cardinal' and 'taylor'"
                     ),
-                }
+                };
+
+                curve_type = Some((rational, ty));
             }
-            "deg" => match parse_args(args)?[..] {
-                [_deg_u, _deg_v] => unimplemented!(),
-                [_deg_u] => unimplemented!(),
+            "deg" => match args {
+                [deg_u, deg_v] => degree = Some((parse_arg(deg_u)?, parse_arg(deg_v)?)),
+                [deg_u] => degree = Some((parse_arg(deg_u)?, 0)),
+                _ => make_error!(WrongNumberOfArguments, "Expected 1 or 2 arguments"),
+            },
+            "bmat" => match args {
+                ["u", matrix @ ..] => basis_matrix.0 = parse_args(matrix)?,
+                ["v", matrix @ ..] => basis_matrix.1 = parse_args(matrix)?,
+                _ => make_error!(WrongTypeOfArguments, "Expected 'u' or 'v' direction"),
+            },
+            "step" => match args {
+                [step_u, step_v] => step = (parse_arg(step_u)?, parse_arg(step_v)?),
+                [step_u] => step = (parse_arg(step_u)?, 1),
                 _ => make_error!(WrongNumberOfArguments, "Expected 1 or 2 arguments"),
             },
-            "bmat" => unimplemented!(),
-            "step" => unimplemented!(),
 
             // Elements
-            "p" => {
-                for v in args {
-                    let v = try_index(&positions, v)?;
-                    points.push(v);
+            "p" => points.extend(parse_points(args, positions.len())?),
+            "l" => lines.push(parse_line(args, positions.len(), tex_coords.len())?),
+            "fo" | "f" => polygons.push(parse_polygon(
+                args,
+                positions.len(),
+                tex_coords.len(),
+                normals.len(),
+            )?),
+            "curv" => {
+                let attributes = free_form_attributes(curve_type, degree, &basis_matrix, step)?;
+
+                match args {
+                    [start, end, vertices @ ..] if vertices.len() >= 2 => {
+                        let vertices = vertices
+                            .iter()
+                            .map(|v| try_index(positions.len(), v))
+                            .collect::<ObjResult<_>>()?;
+
+                        curves.push(Curve {
+                            attributes,
+                            range: (parse_arg(start)?, parse_arg(end)?),
+                            vertices,
+                            parameters: Vec::new(),
+                            special_points: Vec::new(),
+                            technique: curve_technique,
+                        });
+                        body = Some(Body::Curve);
+                    }
+                    _ => make_error!(WrongNumberOfArguments, "Expected at least 4 arguments"),
                 }
             }
-            "l" => match args {
-                [] => make_error!(WrongNumberOfArguments, "Expected at least 2 arguments"),
-                [first, rest @ ..] => {
-                    if args.len() < 2 {
-                        make_error!(WrongNumberOfArguments, "Expected at least 2 arguments")
-                    }
+            "curv2" => {
+                let attributes = free_form_attributes(curve_type, degree, &basis_matrix, step)?;
+
+                if args.len() < 2 {
+                    make_error!(WrongNumberOfArguments, "Expected at least 2 arguments")
+                }
+                let vertices = args
+                    .iter()
+                    .map(|vp| try_index(param_vertices.len(), vp))
+                    .collect::<ObjResult<_>>()?;
 
-                    let line = parse_args! {
-                        first, rest,
-                        [p] => Line::P[try_index(&positions, p)?],
-                        [p, t] => Line::PT[(try_index(&positions, p)?, try_index(&tex_coords, t)?)],
-                        ! => make_error!(WrongTypeOfArguments, "Unexpected vertex format, expected `#`, or `#/#`")
-                    };
+                curves2.push(Curve2 {
+                    attributes,
+                    vertices,
+                    parameters: Vec::new(),
+                    special_points: Vec::new(),
+                });
+                body = Some(Body::Curve2);
+            }
+            "surf" => {
+                let attributes = free_form_attributes(curve_type, degree, &basis_matrix, step)?;
+
+                match args {
+                    [s0, s1, t0, t1, vertices @ ..] if !vertices.is_empty() => {
+                        let vertices = vertices
+                            .iter()
+                            .map(|v| match split_vertex_group(v)[..] {
+                                [p] => Ok((try_index(positions.len(), p)?, None, None)),
+                                [p, t] => Ok((
+                                    try_index(positions.len(), p)?,
+                                    Some(try_index(tex_coords.len(), t)?),
+                                    None,
+                                )),
+                                [p, "", n] => Ok((
+                                    try_index(positions.len(), p)?,
+                                    None,
+                                    Some(try_index(normals.len(), n)?),
+                                )),
+                                [p, t, n] => Ok((
+                                    try_index(positions.len(), p)?,
+                                    Some(try_index(tex_coords.len(), t)?),
+                                    Some(try_index(normals.len(), n)?),
+                                )),
+                                _ => make_error!(
+                                    WrongTypeOfArguments,
+                                    "Unexpected vertex format, expected `#`, `#/#`, `#//#`, or `#/#/#`"
+                                ),
+                            })
+                            .collect::<ObjResult<_>>()?;
 
-                    lines.push(line);
+                        surfaces.push(Surface {
+                            attributes,
+                            range_u: (parse_arg(s0)?, parse_arg(s1)?),
+                            range_v: (parse_arg(t0)?, parse_arg(t1)?),
+                            vertices,
+                            parameters_u: Vec::new(),
+                            parameters_v: Vec::new(),
+                            trims: Vec::new(),
+                            holes: Vec::new(),
+                            special_curves: Vec::new(),
+                            special_points: Vec::new(),
+                            technique: surface_technique,
+                        });
+                        body = Some(Body::Surface);
+                    }
+                    _ => make_error!(WrongNumberOfArguments, "Expected at least 5 arguments"),
                 }
-            },
-            "fo" | "f" => match args {
-                [] => make_error!(WrongNumberOfArguments, "Expected at least 3 arguments"),
-                [first, rest @ ..] => {
-                    if args.len() < 3 {
-                        make_error!(WrongNumberOfArguments, "Expected at least 3 arguments")
+            }
+
+            // Free-form curve / surface body statements
+            "parm" => {
+                let (direction, values) = match args {
+                    [direction, values @ ..] if values.len() >= 2 => {
+                        (*direction, parse_args(values)?)
+                    }
+                    _ => make_error!(WrongNumberOfArguments, "Expected at least 3 arguments"),
+                };
+
+                match (body, direction) {
+                    (Some(Body::Curve), "u") => curves.last_mut().unwrap().parameters = values,
+                    (Some(Body::Curve2), "u") => curves2.last_mut().unwrap().parameters = values,
+                    (Some(Body::Surface), "u") => {
+                        surfaces.last_mut().unwrap().parameters_u = values
                     }
+                    (Some(Body::Surface), "v") => {
+                        surfaces.last_mut().unwrap().parameters_v = values
+                    }
+                    (None, _) => make_error!(
+                        UnexpectedStatement,
+                        "Met a body statement outside of a free-form curve or surface"
+                    ),
+                    _ => make_error!(WrongTypeOfArguments, "Expected 'u' or 'v' direction"),
+                }
+            }
+            "trim" | "hole" | "scrv" => {
+                let surface = match body {
+                    Some(Body::Surface) => surfaces.last_mut().unwrap(),
+                    _ => make_error!(
+                        UnexpectedStatement,
+                        "Expected to be placed in the body of a free-form surface"
+                    ),
+                };
 
-                    let polygon = parse_args! {
-                        first, rest,
-                        [p] => Polygon::P[try_index(&positions, p)?],
-                        [p, t] => Polygon::PT[(try_index(&positions, p)?, try_index(&tex_coords, t)?)],
-                        [p, "", n] => Polygon::PN[(try_index(&positions, p)?, try_index(&normals, n)?)],
-                        [p, t, n] => Polygon::PTN[(try_index(&positions, p)?, try_index(&tex_coords, t)?, try_index(&normals, n)?)],
-                        ! => make_error!(WrongTypeOfArguments, "Unexpected vertex format, expected `#`, `#/#`, `#//#`, or `#/#/#`")
-                    };
+                if args.is_empty() || !args.len().is_multiple_of(3) {
+                    make_error!(
+                        WrongNumberOfArguments,
+                        "Expected a sequence of 'u0 u1 curv2d' triples"
+                    )
+                }
+                let trim_loop = args
+                    .chunks(3)
+                    .map(|triple| {
+                        Ok(TrimCurve {
+                            start: parse_arg(triple[0])?,
+                            end: parse_arg(triple[1])?,
+                            curve: try_index(curves2.len(), triple[2])?,
+                        })
+                    })
+                    .collect::<ObjResult<_>>()?;
 
-                    polygons.push(polygon);
+                match stmt {
+                    "trim" => surface.trims.push(trim_loop),
+                    "hole" => surface.holes.push(trim_loop),
+                    _ => surface.special_curves.push(trim_loop),
                 }
-            },
-            "curv" => unimplemented!(),
-            "curv2" => unimplemented!(),
-            "surf" => unimplemented!(),
+            }
+            "sp" => {
+                let special_points = match body {
+                    Some(Body::Curve) => &mut curves.last_mut().unwrap().special_points,
+                    Some(Body::Curve2) => &mut curves2.last_mut().unwrap().special_points,
+                    Some(Body::Surface) => &mut surfaces.last_mut().unwrap().special_points,
+                    None => make_error!(
+                        UnexpectedStatement,
+                        "Met a body statement outside of a free-form curve or surface"
+                    ),
+                };
 
-            // Free-form curve / surface body statements
-            "parm" => unimplemented!(),
-            "trim" => unimplemented!(),
-            "hole" => unimplemented!(),
-            "scrv" => unimplemented!(),
-            "sp" => unimplemented!(),
-            "end" => unimplemented!(),
+                for vp in args {
+                    special_points.push(try_index(param_vertices.len(), vp)?);
+                }
+            }
+            "end" => match body.take() {
+                Some(_) => {}
+                None => make_error!(
+                    UnexpectedStatement,
+                    "Met an 'end' statement outside of a free-form curve or surface"
+                ),
+            },
 
             // Connectivity between free-form surfaces
-            "con" => unimplemented!(),
+            "con" => match args {
+                [surf_1, q0_1, q1_1, curv2d_1, surf_2, q0_2, q1_2, curv2d_2] => {
+                    connections.push(Connection {
+                        first: (
+                            try_index(surfaces.len(), surf_1)?,
+                            TrimCurve {
+                                start: parse_arg(q0_1)?,
+                                end: parse_arg(q1_1)?,
+                                curve: try_index(curves2.len(), curv2d_1)?,
+                            },
+                        ),
+                        second: (
+                            try_index(surfaces.len(), surf_2)?,
+                            TrimCurve {
+                                start: parse_arg(q0_2)?,
+                                end: parse_arg(q1_2)?,
+                                curve: try_index(curves2.len(), curv2d_2)?,
+                            },
+                        ),
+                    })
+                }
+                _ => make_error!(WrongNumberOfArguments, "Expected 8 arguments"),
+            },
 
             // Grouping
             "g" => match args {
-                [name] => group_builder.start((*name).to_string()),
-                _ => make_error!(
-                    WrongNumberOfArguments,
-                    "Expected group name parameter, but nothing has been supplied"
-                ),
+                [] => group_builder.start(String::from("default")),
+                _ => group_builder.start_all(args.iter().map(|name| name.to_string()).collect()),
             },
-            "s" => match args {
-                ["off"] | ["0"] => smoothing_builder.end(),
-                [param] => smoothing_builder.start(param.parse()?),
-                _ => make_error!(WrongNumberOfArguments, "Expected only 1 argument"),
+            "s" => match parse_group_number(args)? {
+                Some(group) => smoothing_builder.start(group),
+                None => smoothing_builder.end(),
             },
-            "mg" => match args {
-                ["off"] | ["0"] => merging_builder.end(),
-                [param] => merging_builder.start(param.parse()?),
-                _ => make_error!(WrongNumberOfArguments, "Expected only 1 argument"),
+            "mg" => match parse_group_number(args)? {
+                Some(group) => merging_builder.start(group),
+                None => merging_builder.end(),
             },
-            "o" => {
-                name = match args {
-                    [] => None,
-                    _ => Some(args.join(" ")),
-                    // TODO: "name a  b" will be parsed as "name a b"
+            "o" => match args {
+                [] => {
+                    name = None;
+                    object_builder.end();
                 }
-            }
+                _ => {
+                    name = Some(text.to_string());
+                    object_builder.start(text.to_string());
+                }
+            },
+
+            // General statements
+            "call" | "csh" => unrecognized.unsupported(line, stmt, args)?,
 
             // Display / render attributes
-            "bevel" => unimplemented!(),
-            "c_interp" => unimplemented!(),
-            "d_interp" => unimplemented!(),
-            "lod" => unimplemented!(),
-            "usemtl" => match args {
-                [material] => mesh_builder.start((*material).to_string()),
-                _ => make_error!(WrongNumberOfArguments, "Expected only 1 argument"),
-            },
+            "bevel" | "c_interp" | "d_interp" | "lod" | "maplib" | "usemap" | "shadow_obj"
+            | "trace_obj" => unrecognized.unsupported(line, stmt, args)?,
+            "usemtl" => {
+                let material = parse_material(args)?;
+                material_names.insert(material.clone());
+                mesh_builder.start(material);
+            }
             "mtllib" => {
                 material_libraries.reserve(args.len());
                 for &path in args {
                     material_libraries.push(path.to_string());
                 }
             }
-            "shadow_obj" => unimplemented!(),
-            "trace_obj" => unimplemented!(),
-            "ctech" => unimplemented!(),
-            "stech" => unimplemented!(),
+            "ctech" => {
+                curve_technique = Some(match args {
+                    ["cparm", res] => CurveTechnique::Parametric {
+                        resolution: parse_arg(res)?,
+                    },
+                    ["cspace", maxlength] => CurveTechnique::Space {
+                        max_length: parse_arg(maxlength)?,
+                    },
+                    ["curv", maxdist, maxangle] => CurveTechnique::Curvature {
+                        max_distance: parse_arg(maxdist)?,
+                        max_angle: parse_arg(maxangle)?,
+                    },
+                    _ => make_error!(
+                        WrongTypeOfArguments,
+                        "Expected 'cparm res', 'cspace maxlength' or 'curv maxdist maxangle'"
+                    ),
+                })
+            }
+            "stech" => {
+                surface_technique = Some(match args {
+                    ["cparma", ures, vres] => SurfaceTechnique::ParametricA {
+                        resolution_u: parse_arg(ures)?,
+                        resolution_v: parse_arg(vres)?,
+                    },
+                    ["cparmb", uvres] => SurfaceTechnique::ParametricB {
+                        resolution: parse_arg(uvres)?,
+                    },
+                    ["cspace", maxlength] => SurfaceTechnique::Space {
+                        max_length: parse_arg(maxlength)?,
+                    },
+                    ["curv", maxdist, maxangle] => SurfaceTechnique::Curvature {
+                        max_distance: parse_arg(maxdist)?,
+                        max_angle: parse_arg(maxangle)?,
+                    },
+                    _ => make_error!(
+                        WrongTypeOfArguments,
+                        "Expected 'cparma ures vres', 'cparmb uvres', 'cspace maxlength' or 'curv maxdist maxangle'"
+                    ),
+                })
+            }
 
             // Unexpected statement
-            _ => make_error!(UnexpectedStatement, "Received unknown statement"),
+            _ => unrecognized.unknown(line, stmt, args)?,
         }
 
         Ok(())
@@ -267,12 +493,14 @@ pub fn parse_obj<T: BufRead>(input: T) -> ObjResult<RawObj> {
 
     group_builder.end();
     mesh_builder.end();
+    object_builder.end();
     smoothing_builder.end();
     merging_builder.end();
 
     Ok(RawObj {
         name,
         material_libraries,
+        material_names,
 
         positions,
         tex_coords,
@@ -282,133 +510,319 @@ pub fn parse_obj<T: BufRead>(input: T) -> ObjResult<RawObj> {
         points,
         lines,
         polygons,
+        curves,
+        curves2,
+        surfaces,
+        connections,
 
+        objects: object_builder.result,
         groups: group_builder.result,
         meshes: mesh_builder.result,
         smoothing_groups: smoothing_builder.result,
         merging_groups: merging_builder.result,
+
+        unrecognized_statements: unrecognized.statements,
+
+        directory: None,
+    })
+}
+
+/// Kind of the free-form element whose body is being parsed.
+#[derive(Copy, Clone)]
+enum Body {
+    Curve,
+    Curve2,
+    Surface,
+}
+
+/// Parses the arguments of `v`.
+pub(super) fn parse_position(args: &[&str]) -> ObjResult<(f32, f32, f32, f32)> {
+    Ok(match args.len() {
+        4 => parse_floats(args).map(|[x, y, z, w]| (x, y, z, w))?,
+        3 => parse_floats(args).map(|[x, y, z]| (x, y, z, 1.0))?,
+        _ => make_error!(WrongNumberOfArguments, "Expected 3 or 4 arguments"),
+    })
+}
+
+/// Parses the arguments of `vt`.
+pub(super) fn parse_tex_coord(args: &[&str]) -> ObjResult<(f32, f32, f32)> {
+    Ok(match args.len() {
+        3 => parse_floats(args).map(|[u, v, w]| (u, v, w))?,
+        2 => parse_floats(args).map(|[u, v]| (u, v, 0.0))?,
+        1 => parse_floats(args).map(|[u]| (u, 0.0, 0.0))?,
+        _ => make_error!(WrongNumberOfArguments, "Expected 1, 2 or 3 arguments"),
+    })
+}
+
+/// Parses the arguments of `vn`.
+pub(super) fn parse_normal(args: &[&str]) -> ObjResult<(f32, f32, f32)> {
+    Ok(match args.len() {
+        3 => parse_floats(args).map(|[x, y, z]| (x, y, z))?,
+        _ => make_error!(WrongNumberOfArguments, "Expected 3 arguments"),
+    })
+}
+
+/// Parses the arguments of `vp`.
+pub(super) fn parse_param_vertex(args: &[&str]) -> ObjResult<(f32, f32, f32)> {
+    Ok(match args.len() {
+        3 => parse_floats(args).map(|[u, v, w]| (u, v, w))?,
+        2 => parse_floats(args).map(|[u, v]| (u, v, 1.0))?,
+        1 => parse_floats(args).map(|[u]| (u, 0.0, 1.0))?,
+        _ => make_error!(WrongNumberOfArguments, "Expected 1, 2 or 3 arguments"),
+    })
+}
+
+/// Parses the arguments of `p`, given the number of positions parsed so far.
+pub(super) fn parse_points(args: &[&str], positions: usize) -> ObjResult<Vec<Point>> {
+    args.iter().map(|v| try_index(positions, v)).collect()
+}
+
+/// Parses the arguments of `l`, given the number of vertex data parsed so far.
+pub(super) fn parse_line(args: &[&str], positions: usize, tex_coords: usize) -> ObjResult<Line> {
+    match args {
+        [] => make_error!(WrongNumberOfArguments, "Expected at least 2 arguments"),
+        [first, rest @ ..] => {
+            if args.len() < 2 {
+                make_error!(WrongNumberOfArguments, "Expected at least 2 arguments")
+            }
+
+            Ok(parse_args! {
+                first, rest,
+                [p] => Line::P[try_index(positions, p)?],
+                [p, t] => Line::PT[(try_index(positions, p)?, try_index(tex_coords, t)?)],
+                ! => make_error!(WrongTypeOfArguments, "Unexpected vertex format, expected `#`, or `#/#`")
+            })
+        }
+    }
+}
+
+/// Parses the arguments of `f`, given the number of vertex data parsed so far.
+pub(super) fn parse_polygon(
+    args: &[&str],
+    positions: usize,
+    tex_coords: usize,
+    normals: usize,
+) -> ObjResult<Polygon> {
+    match args {
+        [] => make_error!(WrongNumberOfArguments, "Expected at least 3 arguments"),
+        [first, rest @ ..] => {
+            if args.len() < 3 {
+                make_error!(WrongNumberOfArguments, "Expected at least 3 arguments")
+            }
+
+            Ok(parse_args! {
+                first, rest,
+                [p] => Polygon::P[try_index(positions, p)?],
+                [p, t] => Polygon::PT[(try_index(positions, p)?, try_index(tex_coords, t)?)],
+                [p, "", n] => Polygon::PN[(try_index(positions, p)?, try_index(normals, n)?)],
+                [p, t, n] => Polygon::PTN[(try_index(positions, p)?, try_index(tex_coords, t)?, try_index(normals, n)?)],
+                ! => make_error!(WrongTypeOfArguments, "Unexpected vertex format, expected `#`, `#/#`, `#//#`, or `#/#/#`")
+            })
+        }
+    }
+}
+
+/// Parses the arguments of `s` and `mg`. Returns `None` if the group is turned off.
+pub(super) fn parse_group_number(args: &[&str]) -> ObjResult<Option<usize>> {
+    match args {
+        ["off"] | ["0"] => Ok(None),
+        [param] => Ok(Some(parse_arg(param)?)),
+        _ => make_error!(WrongNumberOfArguments, "Expected only 1 argument"),
+    }
+}
+
+/// Parses the arguments of `usemtl`.
+pub(super) fn parse_material(args: &[&str]) -> ObjResult<String> {
+    match args {
+        [material] => Ok((*material).to_string()),
+        _ => make_error!(WrongNumberOfArguments, "Expected only 1 argument"),
+    }
+}
+
+/// Builds the free-form attributes of a new curve or surface from the current state.
+fn free_form_attributes(
+    curve_type: Option<(bool, CurveType)>,
+    degree: Option<(usize, usize)>,
+    basis_matrix: &(Vec<f32>, Vec<f32>),
+    step: (usize, usize),
+) -> ObjResult<FreeFormAttributes> {
+    let (rational, curve_type) = match curve_type {
+        Some(val) => val,
+        None => make_error!(
+            InsufficientData,
+            "Free-form geometry type should be specified with 'cstype' first"
+        ),
+    };
+    let degree = match degree {
+        Some(val) => val,
+        None => make_error!(
+            InsufficientData,
+            "Degree of free-form geometry should be specified with 'deg' first"
+        ),
+    };
+
+    Ok(FreeFormAttributes {
+        rational,
+        curve_type,
+        degree,
+        basis_matrix: basis_matrix.clone(),
+        step,
     })
 }
 
 /// Splits a string with '/'.
-fn split_vertex_group(input: &str) -> Vec<&str> {
-    input.split('/').collect()
+///
+/// At most four components are kept, which are already too many for any vertex format.
+fn split_vertex_group(input: &str) -> VertexGroup<'_> {
+    let mut group = VertexGroup {
+        components: [""; 4],
+        len: 0,
+    };
+    for component in input.split('/').take(4) {
+        group.components[group.len] = component;
+        group.len += 1;
+    }
+    group
+}
+
+/// Components of a vertex group, stored without allocation.
+struct VertexGroup<'a> {
+    components: [&'a str; 4],
+    len: usize,
 }
 
-/// Counts current total count of parsed `points`, `lines` and `polygons`.
-struct Counter {
+impl<'a> Deref for VertexGroup<'a> {
+    type Target = [&'a str];
+
+    fn deref(&self) -> &Self::Target {
+        &self.components[..self.len]
+    }
+}
+
+/// Counts current total count of parsed `points`, `lines`, `polygons`, `curves` and `surfaces`.
+pub(super) struct Counter {
     points: *const Vec<Point>,
     lines: *const Vec<Line>,
     polygons: *const Vec<Polygon>,
+    curves: *const Vec<Curve>,
+    surfaces: *const Vec<Surface>,
 }
 
 impl Counter {
     /// Constructs a new `Counter`.
-    fn new(
+    pub(super) fn new(
         points: *const Vec<Point>,
         lines: *const Vec<Line>,
         polygons: *const Vec<Polygon>,
+        curves: *const Vec<Curve>,
+        surfaces: *const Vec<Surface>,
     ) -> Self {
         Counter {
             points,
             lines,
             polygons,
+            curves,
+            surfaces,
         }
     }
 
-    /// Returns a current count of parsed `(points, lines, polygons)`.
-    fn get(&self) -> (usize, usize, usize) {
+    /// Returns a current count of parsed `(points, lines, polygons, curves, surfaces)`.
+    fn get(&self) -> Count {
         unsafe {
             (
                 (*self.points).len(),
                 (*self.lines).len(),
                 (*self.polygons).len(),
+                (*self.curves).len(),
+                (*self.surfaces).len(),
             )
         }
     }
 }
 
-/// Helper for creating `groups`, `meshes`, `smoothing_groups` and `merging_groups` member of
-/// `Obj`.
-struct GroupBuilder<'a, K> {
+/// Count of parsed `(points, lines, polygons, curves, surfaces)`.
+type Count = (usize, usize, usize, usize, usize);
+
+/// Helper for creating `objects`, `groups`, `meshes`, `smoothing_groups` and `merging_groups`
+/// member of `Obj`.
+pub(super) struct GroupBuilder<'a, K> {
     counter: &'a Counter,
-    /// `Some(K)` if some group has been started, `None` otherwise.
-    current: Option<K>,
-    result: HashMap<K, Group>,
+    /// Groups which have been started and not ended yet.
+    current: Vec<K>,
+    pub(super) result: HashMap<K, Group>,
 }
 
 impl<'a, K> GroupBuilder<'a, K>
 where
     K: Clone + Eq + Hash,
 {
-    fn new(counter: &'a Counter) -> Self {
+    pub(super) fn new(counter: &'a Counter) -> Self {
         GroupBuilder {
             counter,
-            current: None,
+            current: Vec::new(),
             result: HashMap::new(),
         }
     }
 
-    fn with_default(counter: &'a Counter, default: K) -> Self {
+    pub(super) fn with_default(counter: &'a Counter, default: K) -> Self {
         let mut result = HashMap::with_capacity(1);
-        result.insert(default.clone(), Group::new((0, 0, 0)));
+        result.insert(default.clone(), Group::new((0, 0, 0, 0, 0)));
 
         GroupBuilder {
             counter,
-            current: Some(default),
+            current: vec![default],
             result,
         }
     }
 
-    /// Starts a group whose name is `input`.
-    fn start(&mut self, input: K) {
+    /// Starts a group whose name is `input`, and ends the others.
+    pub(super) fn start(&mut self, input: K) {
+        self.start_all(vec![input]);
+    }
+
+    /// Starts groups whose names are `inputs`, and ends the others.
+    pub(super) fn start_all(&mut self, inputs: Vec<K>) {
         let count = self.counter.get();
 
-        match self.current {
+        // Close the past groups which are not started again
+        let (kept, past): (Vec<_>, Vec<_>) = mem::take(&mut self.current)
+            .into_iter()
+            .partition(|current| inputs.contains(current));
+        for current in past {
+            self.close(current, count);
+        }
+        self.current = kept;
+
+        for input in inputs {
             // Started same group twice, do nothing
-            Some(ref current) if *current == input => return,
-            // Changing group
-            Some(ref mut current) => {
-                // Close the past group and start a new one
-                let past = mem::replace(current, input.clone());
-                match self.result.entry(past) {
-                    Entry::Vacant(_) => unreachable!(),
-                    Entry::Occupied(mut e) => {
-                        let was_empty_group = e.get_mut().end(count);
-                        // Remove the past group if the past group is empty
-                        if was_empty_group {
-                            e.remove();
-                        }
-                    }
-                }
+            if self.current.contains(&input) {
+                continue;
             }
-            // Starting a new group
-            None => self.current = Some(input.clone()),
+
+            self.result
+                .entry(input.clone())
+                .and_modify(|e| e.start(count))
+                .or_insert_with(|| Group::new(count));
+            self.current.push(input);
         }
+    }
 
-        self.result
-            .entry(input)
-            .and_modify(|e| e.start(count))
-            .or_insert_with(|| Group::new(count));
+    /// Ends current groups.
+    pub(super) fn end(&mut self) {
+        let count = self.counter.get();
+        for current in mem::take(&mut self.current) {
+            self.close(current, count);
+        }
     }
 
-    /// Ends a current group.
-    fn end(&mut self) {
-        match self.current.take() {
-            // Closed group twice, do nothing
-            None => {}
-            // Closing a group
-            Some(current) => {
-                match self.result.entry(current) {
-                    Entry::Vacant(_) => unreachable!(),
-                    Entry::Occupied(mut e) => {
-                        let count = self.counter.get();
-                        let was_empty_group = e.get_mut().end(count);
-                        // Remove the past group if the past group is empty
-                        if was_empty_group {
-                            e.remove();
-                        }
-                    }
+    fn close(&mut self, current: K, count: Count) {
+        match self.result.entry(current) {
+            Entry::Vacant(_) => unreachable!(),
+            Entry::Occupied(mut e) => {
+                let was_empty_group = e.get_mut().end(count);
+                // Remove the past group if the past group is empty
+                if was_empty_group {
+                    e.remove();
                 }
             }
         }
@@ -419,36 +833,40 @@ where
 const UNDEFINED: usize = usize::MAX;
 
 impl Group {
-    fn new(count: (usize, usize, usize)) -> Self {
+    fn new(count: Count) -> Self {
         let mut ret = Group {
             points: Vec::with_capacity(1),
             lines: Vec::with_capacity(1),
             polygons: Vec::with_capacity(1),
+            curves: Vec::new(),
+            surfaces: Vec::new(),
         };
         ret.start(count);
         ret
     }
 
-    fn start(&mut self, count: (usize, usize, usize)) {
-        self.points.push(Range {
-            start: count.0,
-            end: UNDEFINED,
-        });
-        self.lines.push(Range {
-            start: count.1,
-            end: UNDEFINED,
-        });
-        self.polygons.push(Range {
-            start: count.2,
-            end: UNDEFINED,
-        })
+    fn start(&mut self, count: Count) {
+        start(&mut self.points, count.0);
+        start(&mut self.lines, count.1);
+        start(&mut self.polygons, count.2);
+        start(&mut self.curves, count.3);
+        start(&mut self.surfaces, count.4);
+
+        fn start(vec: &mut Vec<Range>, start: usize) {
+            vec.push(Range {
+                start,
+                end: UNDEFINED,
+            });
+        }
     }
 
     /// Closes group, return true if self is empty
-    fn end(&mut self, count: (usize, usize, usize)) -> bool {
+    fn end(&mut self, count: Count) -> bool {
         end(&mut self.points, count.0);
         end(&mut self.lines, count.1);
         end(&mut self.polygons, count.2);
+        end(&mut self.curves, count.3);
+        end(&mut self.surfaces, count.4);
 
         fn end(vec: &mut Vec<Range>, end: usize) {
             let last = vec.len() - 1;
@@ -460,17 +878,24 @@ impl Group {
             }
         }
 
-        self.points.is_empty() && self.lines.is_empty() && self.polygons.is_empty()
+        self.points.is_empty()
+            && self.lines.is_empty()
+            && self.polygons.is_empty()
+            && self.curves.is_empty()
+            && self.surfaces.is_empty()
     }
 }
 
 /// Low-level Rust binding for `.obj` format.
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct RawObj {
-    /// Name of the object.
+    /// Name of the last object, given by `o`. See `objects` for every object.
     pub name: Option<String>,
     /// `.mtl` files which required by this object.
     pub material_libraries: Vec<String>,
+    /// Names of every material given by `usemtl`. Unlike `meshes`, this includes materials which
+    /// no element follows.
+    pub material_names: HashSet<String>,
 
     /// Position vectors of each vertex.
     pub positions: Vec<(f32, f32, f32, f32)>,
@@ -487,7 +912,17 @@ pub struct RawObj {
     pub lines: Vec<Line>,
     /// Polygons which store the index data of vectors.
     pub polygons: Vec<Polygon>,
+    /// Free-form curves, specified by `curv`.
+    pub curves: Vec<Curve>,
+    /// Free-form 2D curves in the parameter space of surfaces, specified by `curv2`.
+    pub curves2: Vec<Curve2>,
+    /// Free-form surfaces, specified by `surf`.
+    pub surfaces: Vec<Surface>,
+    /// Connectivity between free-form surfaces, specified by `con`.
+    pub connections: Vec<Connection>,
 
+    /// Objects, given by `o`.
+    pub objects: HashMap<String, Group>,
     /// Groups of multiple geometries.
     pub groups: HashMap<String, Group>,
     /// Geometries which consist in a same material.
@@ -496,6 +931,31 @@ pub struct RawObj {
     pub smoothing_groups: HashMap<usize, Group>,
     /// Merging groups.
     pub merging_groups: HashMap<usize, Group>,
+
+    /// Statements which have been ignored according to `ParseOptions`.
+    pub unrecognized_statements: Vec<UnrecognizedStatement>,
+
+    /// Directory of the `.obj` file, if it has been parsed by `parse_obj_file`.
+    pub directory: Option<PathBuf>,
+}
+
+impl RawObj {
+    /// Resolves a path which the `.obj` file refers to, such as a `mtllib`, against `directory`.
+    pub fn resolve_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        match &self.directory {
+            Some(directory) => directory.join(path),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Calls `f` with every object, group, material, smoothing group and merging group.
+    pub(crate) fn for_each_group(&mut self, mut f: impl FnMut(&mut Group)) {
+        self.objects.values_mut().for_each(&mut f);
+        self.groups.values_mut().for_each(&mut f);
+        self.meshes.values_mut().for_each(&mut f);
+        self.smoothing_groups.values_mut().for_each(&mut f);
+        self.merging_groups.values_mut().for_each(&mut f);
+    }
 }
 
 /// The `Point` type which stores the index of the position vector.
@@ -524,7 +984,174 @@ pub enum Polygon {
     PTN(Vec<(usize, usize, usize)>),
 }
 
-/// A group which contains ranges of points, lines and polygons
+/// Type of the basis of free-form geometry, specified by `cstype`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CurveType {
+    /// Arbitrary basis matrix, specified by `bmatrix`.
+    BasisMatrix,
+    /// Bezier basis, specified by `bezier`.
+    Bezier,
+    /// B-spline basis, specified by `bspline`.
+    BSpline,
+    /// Cardinal (Catmull-Rom) basis, specified by `cardinal`.
+    Cardinal,
+    /// Taylor (power) basis, specified by `taylor`.
+    Taylor,
+}
+
+/// Free-form attributes which were in effect when a curve or surface was declared.
+///
+/// Values of the `v` direction are meaningless for curves.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FreeFormAttributes {
+    /// Whether the geometry is rational, specified by `cstype rat`.
+    pub rational: bool,
+    /// Type of the basis, specified by `cstype`.
+    pub curve_type: CurveType,
+    /// Degree in the `u` and `v` direction, specified by `deg`.
+    pub degree: (usize, usize),
+    /// Basis matrices in the `u` and `v` direction, specified by `bmat`. Matrices are stored in
+    /// row-major order, and are empty if they were not specified.
+    pub basis_matrix: (Vec<f32>, Vec<f32>),
+    /// Step sizes in the `u` and `v` direction, specified by `step`.
+    pub step: (usize, usize),
+}
+
+/// A free-form curve in 3D space, specified by `curv`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Curve {
+    /// Attributes of the curve.
+    pub attributes: FreeFormAttributes,
+    /// Starting and ending parameter values of the curve.
+    pub range: (f32, f32),
+    /// Indices of the position vectors used as control points.
+    pub vertices: Vec<usize>,
+    /// Global parameter values, specified by `parm u`.
+    pub parameters: Vec<f32>,
+    /// Indices of the parametric vertices used as special points, specified by `sp`.
+    pub special_points: Vec<usize>,
+    /// Approximation technique, specified by `ctech`.
+    pub technique: Option<CurveTechnique>,
+}
+
+/// A free-form 2D curve in the parameter space of a surface, specified by `curv2`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Curve2 {
+    /// Attributes of the curve.
+    pub attributes: FreeFormAttributes,
+    /// Indices of the parametric vertices used as control points.
+    pub vertices: Vec<usize>,
+    /// Global parameter values, specified by `parm u`.
+    pub parameters: Vec<f32>,
+    /// Indices of the parametric vertices used as special points, specified by `sp`.
+    pub special_points: Vec<usize>,
+}
+
+/// A free-form surface, specified by `surf`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Surface {
+    /// Attributes of the surface.
+    pub attributes: FreeFormAttributes,
+    /// Starting and ending parameter values in the `u` direction.
+    pub range_u: (f32, f32),
+    /// Starting and ending parameter values in the `v` direction.
+    pub range_v: (f32, f32),
+    /// Control points which store the index of the position vector, and optionally the indices
+    /// of the texture coordinate and the normal vector. The `u` direction varies fastest.
+    pub vertices: Vec<(usize, Option<usize>, Option<usize>)>,
+    /// Global parameter values in the `u` direction, specified by `parm u`.
+    pub parameters_u: Vec<f32>,
+    /// Global parameter values in the `v` direction, specified by `parm v`.
+    pub parameters_v: Vec<f32>,
+    /// Outer trimming loops, specified by `trim`.
+    pub trims: Vec<Vec<TrimCurve>>,
+    /// Inner trimming loops, specified by `hole`.
+    pub holes: Vec<Vec<TrimCurve>>,
+    /// Special curves which should be included in the tessellation, specified by `scrv`.
+    pub special_curves: Vec<Vec<TrimCurve>>,
+    /// Indices of the parametric vertices used as special points, specified by `sp`.
+    pub special_points: Vec<usize>,
+    /// Approximation technique, specified by `stech`.
+    pub technique: Option<SurfaceTechnique>,
+}
+
+/// Curve approximation technique, specified by `ctech`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CurveTechnique {
+    /// Constant parametric subdivision, specified by `ctech cparm`. Each polynomial segment is
+    /// subdivided `resolution * degree` times.
+    Parametric {
+        /// Resolution factor.
+        resolution: f32,
+    },
+    /// Constant spatial subdivision, specified by `ctech cspace`.
+    Space {
+        /// Maximum length of line segments.
+        max_length: f32,
+    },
+    /// Curvature-dependent subdivision, specified by `ctech curv`.
+    Curvature {
+        /// Maximum distance between the curve and the approximating line segments.
+        max_distance: f32,
+        /// Maximum angle in degrees between the tangents at the ends of a line segment.
+        max_angle: f32,
+    },
+}
+
+/// Surface approximation technique, specified by `stech`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SurfaceTechnique {
+    /// Constant parametric subdivision with separate resolutions, specified by `stech cparma`.
+    /// Each polynomial segment is subdivided `resolution * degree` times in each direction.
+    ParametricA {
+        /// Resolution factor in the `u` direction.
+        resolution_u: f32,
+        /// Resolution factor in the `v` direction.
+        resolution_v: f32,
+    },
+    /// Constant parametric subdivision with a single resolution, specified by `stech cparmb`.
+    ParametricB {
+        /// Resolution factor in both directions.
+        resolution: f32,
+    },
+    /// Constant spatial subdivision, specified by `stech cspace`.
+    Space {
+        /// Maximum length of edges.
+        max_length: f32,
+    },
+    /// Curvature-dependent subdivision, specified by `stech curv`.
+    Curvature {
+        /// Maximum distance between the surface and the approximating polygons.
+        max_distance: f32,
+        /// Maximum angle in degrees between the normals at the corners of a polygon.
+        max_angle: f32,
+    },
+}
+
+/// A segment of a 2D curve which forms a trimming loop or a special curve.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TrimCurve {
+    /// Starting parameter value of the segment.
+    pub start: f32,
+    /// Ending parameter value of the segment.
+    pub end: f32,
+    /// Index of the 2D curve in `curves2`.
+    pub curve: usize,
+}
+
+/// Connectivity between two free-form surfaces, specified by `con`.
+///
+/// Each side stores the index of the surface in `surfaces`, and the segment of a 2D curve which
+/// lies on the shared edge.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Connection {
+    /// The first surface and its edge curve.
+    pub first: (usize, TrimCurve),
+    /// The second surface and its edge curve.
+    pub second: (usize, TrimCurve),
+}
+
+/// A group which contains ranges of points, lines, polygons, curves and surfaces
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
 pub struct Group {
     /// Multiple range of points
@@ -533,6 +1160,10 @@ pub struct Group {
     pub lines: Vec<Range>,
     /// Multiple range of polygons
     pub polygons: Vec<Range>,
+    /// Multiple range of free-form curves
+    pub curves: Vec<Range>,
+    /// Multiple range of free-form surfaces
+    pub surfaces: Vec<Range>,
 }
 
 /// A struct which represent `[start, end)` range.
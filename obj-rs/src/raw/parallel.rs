@@ -0,0 +1,354 @@
+//! Parses `.obj` format on several threads
+
+use crate::error::ObjResult;
+use crate::raw::lexer::{SliceLines, is_continued, lex_at};
+use crate::raw::object::{
+    Counter, Curve, GroupBuilder, Line, Point, Polygon, RawObj, Surface,
+    parse_obj_bytes_with_options,
+};
+use crate::raw::stream::{EventParser, ObjEvent};
+use crate::raw::{ParseOptions, UnrecognizedStatement};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// Parts smaller than this are not worth a thread of their own.
+const MIN_PART_LEN: usize = 1 << 20;
+
+/// Parses a wavefront `.obj` format from a buffer in memory, on several threads.
+pub fn parse_obj_parallel(input: &[u8]) -> ObjResult<RawObj> {
+    parse_obj_parallel_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.obj` format from a buffer in memory on several threads, with the given
+/// options.
+///
+/// The input is split at line boundaries into parts, which are parsed in parallel and then merged
+/// into exactly what `parse_obj_bytes` returns, errors included. Files with free-form geometry,
+/// whose statements depend on each other too much, are parsed on the current thread.
+pub fn parse_obj_parallel_with_options(input: &[u8], options: &ParseOptions) -> ObjResult<RawObj> {
+    let len = input.len() / (rayon::current_num_threads() * 4);
+    parse_parts(input, len.max(MIN_PART_LEN), options)
+}
+
+/// Parses `input` split into parts of about `len` bytes.
+fn parse_parts(input: &[u8], len: usize, options: &ParseOptions) -> ObjResult<RawObj> {
+    let parts = split(input, len);
+    if parts.len() < 2 {
+        return parse_obj_bytes_with_options(input, options);
+    }
+
+    // Every part has to know the numbers of lines and vertex data before it, to report errors
+    // and resolve indices the way a sequential parser does.
+    let counts = parts.par_iter().map(|part| count(part)).collect::<Vec<_>>();
+    let starts = counts
+        .iter()
+        .scan(Count::default(), |start, count| {
+            let current = *start;
+            start.lines += count.lines;
+            start.positions += count.positions;
+            start.tex_coords += count.tex_coords;
+            start.normals += count.normals;
+            Some(current)
+        })
+        .collect::<Vec<_>>();
+
+    let results = parts
+        .par_iter()
+        .zip(starts)
+        .map(|(part, start)| parse_part(part, start, options))
+        .collect::<Vec<_>>();
+
+    // The first failed part is the one where a sequential parser fails
+    let mut parsed = Vec::with_capacity(results.len());
+    for result in results {
+        match result? {
+            Some(part) => parsed.push(part),
+            None => return parse_obj_bytes_with_options(input, options),
+        }
+    }
+
+    Ok(merge(parsed))
+}
+
+/// Splits `input` into parts of about `len` bytes, at the ends of logical lines.
+fn split(input: &[u8], len: usize) -> Vec<&[u8]> {
+    let mut parts = Vec::new();
+    let mut rest = input;
+    while rest.len() > len {
+        // Find the end of the line which contains the `len`th byte, skipping lines which are
+        // connected with backslashes
+        let mut end = len;
+        loop {
+            let Some(idx) = rest[end..].iter().position(|&b| b == b'\n') else {
+                end = rest.len();
+                break;
+            };
+            let newline = end + idx;
+            let start = rest[..newline]
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |idx| idx + 1);
+
+            end = newline + 1;
+            if !is_continued(&rest[start..newline]) {
+                break;
+            }
+        }
+
+        let (part, tail) = rest.split_at(end);
+        parts.push(part);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
+
+/// Numbers of lines and vertex data in a part.
+#[derive(Copy, Clone, Default)]
+struct Count {
+    lines: usize,
+    positions: usize,
+    tex_coords: usize,
+    normals: usize,
+}
+
+/// Counts the lines and vertex data of a part, without parsing them.
+fn count(input: &[u8]) -> Count {
+    let mut count = Count::default();
+    let mut lines = SliceLines::new(input, 0);
+    let mut continued = false;
+    for line in lines.by_ref() {
+        // Lines which cannot be read are reported by `parse_part`
+        let Ok(line) = line else {
+            continued = false;
+            continue;
+        };
+        if !continued {
+            match line.split_whitespace().next() {
+                Some("v") => count.positions += 1,
+                Some("vt") => count.tex_coords += 1,
+                Some("vn") => count.normals += 1,
+                _ => {}
+            }
+        }
+        continued = line.ends_with('\\');
+    }
+    count.lines = lines.line;
+    count
+}
+
+/// Elements and statements of a part.
+#[derive(Default)]
+struct Part {
+    material_libraries: Vec<String>,
+
+    positions: Vec<(f32, f32, f32, f32)>,
+    tex_coords: Vec<(f32, f32, f32)>,
+    normals: Vec<(f32, f32, f32)>,
+    param_vertices: Vec<(f32, f32, f32)>,
+
+    points: Vec<Point>,
+    lines: Vec<Line>,
+    polygons: Vec<Polygon>,
+
+    /// Statements which start or end groups, with the numbers of points, lines and polygons of
+    /// the part before them.
+    groupings: Vec<((usize, usize, usize), ObjEvent)>,
+
+    unrecognized_statements: Vec<UnrecognizedStatement>,
+}
+
+/// Parses a part, which starts after the lines and vertex data of `start`. Returns `None` if the
+/// part contains free-form geometry.
+fn parse_part(input: &[u8], start: Count, options: &ParseOptions) -> ObjResult<Option<Part>> {
+    let mut parser = EventParser::new(options, (start.positions, start.tex_coords, start.normals));
+    let mut part = Part::default();
+    let mut free_form = false;
+
+    let result = lex_at(input, start.lines, |line, stmt, args, text| {
+        let Some(event) = parser.parse(line, stmt, args, text)? else {
+            return Ok(());
+        };
+        match event {
+            ObjEvent::Position(x, y, z, w) => part.positions.push((x, y, z, w)),
+            ObjEvent::TexCoord(u, v, w) => part.tex_coords.push((u, v, w)),
+            ObjEvent::Normal(x, y, z) => part.normals.push((x, y, z)),
+            ObjEvent::ParamVertex(u, v, w) => part.param_vertices.push((u, v, w)),
+            ObjEvent::Points(mut points) => part.points.append(&mut points),
+            ObjEvent::Line(line) => part.lines.push(line),
+            ObjEvent::Face(polygon) => part.polygons.push(polygon),
+            ObjEvent::MtlLib(mut paths) => part.material_libraries.append(&mut paths),
+            ObjEvent::Unrecognized(statement) => part.unrecognized_statements.push(statement),
+            ObjEvent::FreeForm(_) => free_form = true,
+            event @ (ObjEvent::Group(_)
+            | ObjEvent::Object(_)
+            | ObjEvent::UseMtl(_)
+            | ObjEvent::Smoothing(_)
+            | ObjEvent::Merging(_)) => {
+                let count = (part.points.len(), part.lines.len(), part.polygons.len());
+                part.groupings.push((count, event));
+            }
+        }
+        Ok(())
+    });
+
+    // An error after free-form geometry might not be an error for a sequential parser
+    if free_form {
+        return Ok(None);
+    }
+    result?;
+    Ok(Some(part))
+}
+
+/// Merges the parts in order, starting and ending groups where a sequential parser does.
+fn merge(parts: Vec<Part>) -> RawObj {
+    let mut name = None;
+    let mut material_libraries = Vec::new();
+    let mut material_names = HashSet::new();
+
+    let mut positions = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut normals = Vec::new();
+    let mut param_vertices = Vec::new();
+
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
+    let mut polygons = Vec::new();
+    let curves = Vec::<Curve>::new();
+    let surfaces = Vec::<Surface>::new();
+
+    let mut unrecognized_statements = Vec::new();
+
+    let counter = Counter::new(&points, &lines, &polygons, &curves, &surfaces);
+    let mut object_builder = GroupBuilder::new(&counter);
+    let mut group_builder = GroupBuilder::with_default(&counter, String::from("default"));
+    let mut mesh_builder = GroupBuilder::with_default(&counter, String::new());
+    let mut smoothing_builder = GroupBuilder::new(&counter);
+    let mut merging_builder = GroupBuilder::new(&counter);
+
+    for mut part in parts {
+        material_libraries.append(&mut part.material_libraries);
+        positions.append(&mut part.positions);
+        tex_coords.append(&mut part.tex_coords);
+        normals.append(&mut part.normals);
+        param_vertices.append(&mut part.param_vertices);
+        unrecognized_statements.append(&mut part.unrecognized_statements);
+
+        let mut part_points = part.points.into_iter();
+        let mut part_lines = part.lines.into_iter();
+        let mut part_polygons = part.polygons.into_iter();
+        let mut merged = (0, 0, 0);
+        for (count, event) in part.groupings {
+            points.extend(part_points.by_ref().take(count.0 - merged.0));
+            lines.extend(part_lines.by_ref().take(count.1 - merged.1));
+            polygons.extend(part_polygons.by_ref().take(count.2 - merged.2));
+            merged = count;
+
+            match event {
+                ObjEvent::Group(names) => group_builder.start_all(names),
+                ObjEvent::Smoothing(Some(group)) => smoothing_builder.start(group),
+                ObjEvent::Smoothing(None) => smoothing_builder.end(),
+                ObjEvent::Merging(Some(group)) => merging_builder.start(group),
+                ObjEvent::Merging(None) => merging_builder.end(),
+                ObjEvent::Object(Some(object)) => {
+                    name = Some(object.clone());
+                    object_builder.start(object);
+                }
+                ObjEvent::Object(None) => {
+                    name = None;
+                    object_builder.end();
+                }
+                ObjEvent::UseMtl(material) => {
+                    material_names.insert(material.clone());
+                    mesh_builder.start(material);
+                }
+                _ => unreachable!("Only grouping statements are recorded"),
+            }
+        }
+        points.extend(part_points);
+        lines.extend(part_lines);
+        polygons.extend(part_polygons);
+    }
+
+    group_builder.end();
+    mesh_builder.end();
+    object_builder.end();
+    smoothing_builder.end();
+    merging_builder.end();
+
+    RawObj {
+        name,
+        material_libraries,
+        material_names,
+
+        positions,
+        tex_coords,
+        normals,
+        param_vertices,
+
+        points,
+        lines,
+        polygons,
+        curves,
+        surfaces,
+
+        objects: object_builder.result,
+        groups: group_builder.result,
+        meshes: mesh_builder.result,
+        smoothing_groups: smoothing_builder.result,
+        merging_groups: merging_builder.result,
+
+        unrecognized_statements,
+        ..RawObj::default()
+    }
+}
+
+#[test]
+fn test_parse_parts() {
+    use crate::raw::StatementPolicy;
+
+    let fixtures: [&[u8]; 6] = [
+        include_bytes!("../../tests/fixtures/cube.obj"),
+        include_bytes!("../../tests/fixtures/dome.obj"),
+        include_bytes!("../../tests/fixtures/group.obj"),
+        include_bytes!("../../tests/fixtures/multi-group.obj"),
+        include_bytes!("../../tests/fixtures/objects.obj"),
+        include_bytes!("../../tests/fixtures/scene.obj"),
+    ];
+    let options = ParseOptions {
+        unsupported: StatementPolicy::Collect,
+        unknown: StatementPolicy::Collect,
+    };
+    for input in fixtures {
+        let expected = parse_obj_bytes_with_options(input, &options).unwrap();
+        for len in [1, 16, 64, 256] {
+            assert_eq!(parse_parts(input, len, &options).unwrap(), expected);
+        }
+    }
+
+    // Relative indices and connected lines across parts
+    let input = b"v 0 0 0\nv 1 0 0\ng a\nv 1 \\\n1 0\nf -3 -2 -1\ns 1\nf 1 2 3\nlod 1\ng b\n";
+    let expected = parse_obj_bytes_with_options(input, &options).unwrap();
+    for len in 1..input.len() {
+        assert_eq!(parse_parts(input, len, &options).unwrap(), expected);
+    }
+
+    // Errors are the ones of the first failed line
+    let input = b"v 0 0 0\nf 1 2 3\nv 1 1 1\nfoo\n";
+    for len in 1..input.len() {
+        let e = parse_parts(input, len, &ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            parse_obj_bytes_with_options(input, &ParseOptions::default())
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    // Free-form geometry is parsed sequentially
+    let input = include_bytes!("../../tests/fixtures/freeform.obj");
+    let expected = parse_obj_bytes_with_options(input, &options).unwrap();
+    assert_eq!(parse_parts(input, 16, &options).unwrap(), expected);
+}
@@ -0,0 +1,105 @@
+//! Converts Phong materials of `.mtl` files into metallic-roughness materials
+
+use crate::raw::material::{Material, MtlColor, MtlTextureMap};
+
+/// A material of the metallic-roughness model, as used by glTF and most physically based
+/// renderers. Created by `Material::to_pbr`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PbrMaterial {
+    /// The base color, in linear RGB
+    pub base_color: [f32; 3],
+    /// The base color map
+    pub base_color_map: Option<MtlTextureMap>,
+    /// The metalness, from 0 (dielectric) to 1 (metal)
+    pub metallic: f32,
+    /// The metalness map
+    pub metallic_map: Option<MtlTextureMap>,
+    /// The perceptual roughness, from 0 (smooth) to 1 (rough)
+    pub roughness: f32,
+    /// The roughness map
+    pub roughness_map: Option<MtlTextureMap>,
+    /// The emissive color, in linear RGB
+    pub emissive: [f32; 3],
+    /// The emissive color map
+    pub emissive_map: Option<MtlTextureMap>,
+    /// The opacity, from 0 (transparent) to 1 (opaque)
+    pub opacity: f32,
+    /// The opacity map
+    pub opacity_map: Option<MtlTextureMap>,
+    /// The tangent space normal map
+    pub normal_map: Option<MtlTextureMap>,
+    /// How the opacity is applied
+    pub alpha_mode: AlphaMode,
+}
+
+/// How the opacity of a `PbrMaterial` is applied.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum AlphaMode {
+    /// The opacity is ignored
+    #[default]
+    Opaque,
+    /// Fragments are either fully opaque or discarded, depending on the opacity map
+    Mask,
+    /// The material is blended with the background
+    Blend,
+}
+
+impl Material {
+    /// Converts the material into the metallic-roughness model.
+    ///
+    /// Fields of the PBR extension (`Pr`, `Pm`, `map_Pr`, `map_Pm` and `norm`) are used when
+    /// present. Otherwise, the following heuristics apply:
+    ///
+    /// - The base color is `Kd`, or white if it is missing.
+    /// - The metalness is 1 for the illumination model 3 (mirror reflection), 0 otherwise.
+    /// - The roughness is derived from `Ns` with the Blinn-Phong to Beckmann equivalence,
+    ///   `(2 / (Ns + 2))^(1/4)`, or is 1 if `Ns` is missing.
+    /// - The normal map is the `bump` map, which exporters commonly use for normal maps.
+    /// - The opacity is `d` (or `1 - Tr`). The material is blended if it is below 1, masked if it
+    ///   only has a `map_d`, and opaque otherwise.
+    ///
+    /// Colors in CIEXYZ are converted with `MtlColor::to_rgb`. Spectral colors are ignored, as if
+    /// they were missing, since their `.rfl` files are not loaded.
+    pub fn to_pbr(&self) -> PbrMaterial {
+        let metallic = self
+            .metallic
+            .unwrap_or(if self.illumination_model == Some(3) {
+                1.0
+            } else {
+                0.0
+            });
+        let roughness = match (self.roughness, self.specular_exponent) {
+            (Some(roughness), _) => roughness,
+            (None, Some(exponent)) => (2.0 / (exponent.max(0.0) + 2.0)).powf(0.25),
+            (None, None) => 1.0,
+        };
+        let opacity = self.dissolve.unwrap_or(1.0).clamp(0.0, 1.0);
+        let alpha_mode = if opacity < 1.0 {
+            AlphaMode::Blend
+        } else if self.dissolve_map.is_some() {
+            AlphaMode::Mask
+        } else {
+            AlphaMode::Opaque
+        };
+
+        PbrMaterial {
+            base_color: rgb(&self.diffuse).unwrap_or([1.0; 3]),
+            base_color_map: self.diffuse_map.clone(),
+            metallic: metallic.clamp(0.0, 1.0),
+            metallic_map: self.metallic_map.clone(),
+            roughness: roughness.clamp(0.0, 1.0),
+            roughness_map: self.roughness_map.clone(),
+            emissive: rgb(&self.emissive).unwrap_or([0.0; 3]),
+            emissive_map: self.emissive_map.clone(),
+            opacity,
+            opacity_map: self.dissolve_map.clone(),
+            normal_map: self.normal_map.as_ref().or(self.bump_map.as_ref()).cloned(),
+            alpha_mode,
+        }
+    }
+}
+
+/// Converts a color into linear sRGB, unless it is missing or spectral.
+fn rgb(color: &Option<MtlColor>) -> Option<[f32; 3]> {
+    color.as_ref()?.to_rgb(None)
+}
@@ -0,0 +1,38 @@
+//! Reads the files which `.obj` and `.mtl` files refer to
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// Source of the files which `.obj` and `.mtl` files refer to: `.mtl` files of `mtllib`
+/// statements, texture maps and `.rfl` files of spectral colors.
+///
+/// Implement this to load them from packed archives or other virtual file systems.
+pub trait Resolver {
+    /// Reads the whole file at `path`.
+    fn read(&mut self, path: &Path) -> Result<Vec<u8>>;
+}
+
+/// `Resolver` which reads files from the file system of the OS.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct FileSystem;
+
+impl Resolver for FileSystem {
+    fn read(&mut self, path: &Path) -> Result<Vec<u8>> {
+        fs::read(path)
+    }
+}
+
+/// Files in memory, keyed by their paths.
+impl Resolver for HashMap<PathBuf, Vec<u8>> {
+    fn read(&mut self, path: &Path) -> Result<Vec<u8>> {
+        match self.get(path) {
+            Some(file) => Ok(file.clone()),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} is not in the map", path.display()),
+            )),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Parses `.rfl` files of spectral colors and converts colors into linear sRGB
+
+use crate::error::{ObjResult, make_error};
+use crate::raw::lexer::{Callback, lex, lex_read};
+use crate::raw::material::MtlColor;
+use crate::raw::util::parse_arg;
+use std::cmp::Ordering;
+use std::io::BufRead;
+
+/// Parses a `.rfl` file, which `MtlColor::Spectral` refers to.
+///
+/// The file consists of `min` and `max` statements, which specify the wavelengths of the first
+/// and the last sample in nanometers, and of the samples themselves, which are spread evenly
+/// between them. Samples may be written on any number of lines.
+///
+/// ```text
+/// # A reddish reflectance curve
+/// min 400
+/// max 700
+/// 0.05 0.05 0.1 0.2
+/// 0.6 0.8 0.85
+/// ```
+pub fn parse_rfl<T: BufRead>(input: T) -> ObjResult<SpectralCurve> {
+    parse_rfl_lexed(|callback| lex_read(input, callback))
+}
+
+/// Parses a `.rfl` file from a buffer in memory. See `parse_rfl` for the format.
+pub fn parse_rfl_bytes(input: &[u8]) -> ObjResult<SpectralCurve> {
+    parse_rfl_lexed(|callback| lex(input, callback))
+}
+
+/// Parses the statements which `lex` calls back with.
+fn parse_rfl_lexed<L>(lex: L) -> ObjResult<SpectralCurve>
+where
+    L: FnOnce(&mut Callback) -> ObjResult<()>,
+{
+    let mut min = None;
+    let mut max = None;
+    let mut values = Vec::new();
+
+    lex(&mut |_, stmt, args, _| {
+        match stmt {
+            "min" => match args {
+                [arg] => min = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+            "max" => match args {
+                [arg] => max = Some(parse_arg(arg)?),
+                _ => make_error!(WrongNumberOfArguments, "Expected exactly 1 argument"),
+            },
+
+            // Samples, of which the first one is lexed as the statement
+            _ => match parse_arg::<f32>(stmt) {
+                Ok(value) => {
+                    values.push(value);
+                    for arg in args {
+                        values.push(parse_arg(arg)?);
+                    }
+                }
+                Err(_) => make_error!(UnexpectedStatement, "Received unknown statement"),
+            },
+        }
+
+        Ok(())
+    })?;
+
+    match (min, max) {
+        (Some(min), Some(max)) if min < max && values.len() >= 2 => {
+            Ok(SpectralCurve { min, max, values })
+        }
+        (Some(_), Some(_)) => make_error!(
+            InsufficientData,
+            "Expected at least 2 samples over a wavelength range"
+        ),
+        _ => make_error!(InsufficientData, "Expected `min` and `max` statements"),
+    }
+}
+
+/// A spectral reflectance curve, parsed from a `.rfl` file by `parse_rfl`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpectralCurve {
+    /// Wavelength of the first sample, in nanometers
+    pub min: f32,
+    /// Wavelength of the last sample, in nanometers
+    pub max: f32,
+    /// Reflectances, sampled evenly from `min` to `max`
+    pub values: Vec<f32>,
+}
+
+impl SpectralCurve {
+    /// Returns the reflectance at `wavelength`, interpolated linearly between the samples.
+    /// Outside of the sampled range, the nearest sample is returned.
+    ///
+    /// Curves which `parse_rfl` would reject are handled too: a curve without samples reflects
+    /// nothing, and a curve with a single sample or an empty range reflects its first sample at
+    /// every wavelength.
+    pub fn reflectance(&self, wavelength: f32) -> f32 {
+        let Some(last) = self.values.len().checked_sub(1) else {
+            return 0.0;
+        };
+        if last == 0 || self.min.partial_cmp(&self.max) != Some(Ordering::Less) {
+            return self.values[0];
+        }
+
+        let position = (wavelength - self.min) / (self.max - self.min) * last as f32;
+        if position <= 0.0 {
+            return self.values[0];
+        }
+        if position >= last as f32 {
+            return self.values[last];
+        }
+
+        let index = position as usize;
+        let t = position - index as f32;
+        self.values[index] * (1.0 - t) + self.values[index + 1] * t
+    }
+
+    /// Converts the curve into CIEXYZ, integrating it against the CIE 1931 standard observer
+    /// under the D65 illuminant. A perfect reflector results in the D65 white point, of which Y
+    /// is 1.
+    pub fn to_xyz(&self) -> [f32; 3] {
+        let mut xyz = [0.0; 3];
+        let mut white = 0.0;
+        for (i, (observer, illuminant)) in CIE_1931_OBSERVER.iter().zip(D65).enumerate() {
+            let reflectance = self.reflectance(CIE_MIN + CIE_STEP * i as f32);
+            for (sum, weight) in xyz.iter_mut().zip(observer) {
+                *sum += reflectance * illuminant * weight;
+            }
+            white += illuminant * observer[1];
+        }
+        xyz.map(|sum| sum / white)
+    }
+
+    /// Converts the curve into linear sRGB.
+    pub fn to_rgb(&self) -> [f32; 3] {
+        xyz_to_rgb(self.to_xyz())
+    }
+}
+
+impl MtlColor {
+    /// Converts the color into linear sRGB.
+    ///
+    /// `Xyz` colors are converted without chromatic adaptation. `Spectral` colors need the curve
+    /// of their `.rfl` file, or `None` is returned; `MaterialLibrary::to_rgb` loads it.
+    pub fn to_rgb(&self, curve: Option<&SpectralCurve>) -> Option<[f32; 3]> {
+        match *self {
+            MtlColor::Rgb(r, g, b) => Some([r, g, b]),
+            MtlColor::Xyz(x, y, z) => Some(xyz_to_rgb([x, y, z])),
+            MtlColor::Spectral(_, multiplier) => {
+                curve.map(|curve| curve.to_rgb().map(|value| value * multiplier))
+            }
+        }
+    }
+}
+
+/// Converts a CIEXYZ color into linear sRGB, whose white point is D65.
+fn xyz_to_rgb([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    ]
+}
+
+/// Wavelength of the first sample of the tables below, in nanometers.
+const CIE_MIN: f32 = 380.0;
+/// Distance between the samples of the tables below, in nanometers.
+const CIE_STEP: f32 = 10.0;
+
+/// The CIE 1931 2° standard observer, x̄, ȳ and z̄ from 380 nm to 780 nm.
+#[allow(clippy::excessive_precision)] // Values as tabulated by the CIE
+const CIE_1931_OBSERVER: [[f32; 3]; 41] = [
+    [0.001368, 0.000039, 0.006450],
+    [0.004243, 0.000120, 0.020050],
+    [0.014310, 0.000396, 0.067850],
+    [0.043510, 0.001210, 0.207400],
+    [0.134380, 0.004000, 0.645600],
+    [0.283900, 0.011600, 1.385600],
+    [0.348280, 0.023000, 1.747060],
+    [0.336200, 0.038000, 1.772110],
+    [0.290800, 0.060000, 1.669200],
+    [0.195360, 0.090980, 1.287640],
+    [0.095640, 0.139020, 0.812950],
+    [0.032010, 0.208020, 0.465180],
+    [0.004900, 0.323000, 0.272000],
+    [0.009300, 0.503000, 0.158200],
+    [0.063270, 0.710000, 0.078250],
+    [0.165500, 0.862000, 0.042160],
+    [0.290400, 0.954000, 0.020300],
+    [0.433450, 0.994950, 0.008750],
+    [0.594500, 0.995000, 0.003900],
+    [0.762100, 0.952000, 0.002100],
+    [0.916300, 0.870000, 0.001650],
+    [1.026300, 0.757000, 0.001100],
+    [1.062200, 0.631000, 0.000800],
+    [1.002600, 0.503000, 0.000340],
+    [0.854450, 0.381000, 0.000190],
+    [0.642400, 0.265000, 0.000050],
+    [0.447900, 0.175000, 0.000020],
+    [0.283500, 0.107000, 0.000000],
+    [0.164900, 0.061000, 0.000000],
+    [0.087400, 0.032000, 0.000000],
+    [0.046770, 0.017000, 0.000000],
+    [0.022700, 0.008210, 0.000000],
+    [0.011359, 0.004102, 0.000000],
+    [0.005790, 0.002091, 0.000000],
+    [0.002899, 0.001047, 0.000000],
+    [0.001440, 0.000520, 0.000000],
+    [0.000690, 0.000249, 0.000000],
+    [0.000332, 0.000120, 0.000000],
+    [0.000166, 0.000060, 0.000000],
+    [0.000083, 0.000030, 0.000000],
+    [0.000042, 0.000015, 0.000000],
+];
+
+/// Relative spectral power of the CIE standard illuminant D65 from 380 nm to 780 nm.
+const D65: [f32; 41] = [
+    49.9755, 54.6482, 82.7549, 91.486, 93.4318, 86.6823, 104.865, 117.008, 117.812, 114.861,
+    115.923, 108.811, 109.354, 107.802, 104.790, 107.689, 104.405, 104.046, 100.000, 96.3342,
+    95.788, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146, 82.2778,
+    78.2842, 69.7213, 71.6091, 74.349, 61.604, 69.8856, 75.087, 63.5927, 46.4182, 66.8054, 63.3828,
+];
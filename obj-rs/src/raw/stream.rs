@@ -0,0 +1,239 @@
+//! Parses `.obj` format into a stream of events, without storing them
+
+use crate::error::ObjResult;
+use crate::raw::lexer::{Blocks, lex_at};
+use crate::raw::object::{
+    Line, Point, Polygon, parse_group_number, parse_line, parse_material, parse_normal,
+    parse_param_vertex, parse_points, parse_polygon, parse_position, parse_tex_coord,
+};
+use crate::raw::{ParseOptions, Unrecognized, UnrecognizedStatement};
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+/// Parses a wavefront `.obj` format into a stream of events.
+///
+/// Unlike `parse_obj`, no vertex data or elements are stored, so files of any size can be parsed
+/// with constant memory. The input is read in blocks of about 64 KiB, which are lexed without
+/// allocating per line. Indices of elements are resolved into 0-based indices just like
+/// `parse_obj` does.
+///
+/// A statement which fails to parse results in an error, after which the following statements
+/// are still parsed. An I/O error or a line which is not valid UTF-8 ends the iteration.
+pub fn parse_obj_events<T: BufRead>(input: T) -> ObjEvents<T> {
+    parse_obj_events_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a wavefront `.obj` format into a stream of events, with the given options.
+pub fn parse_obj_events_with_options<T: BufRead>(input: T, options: &ParseOptions) -> ObjEvents<T> {
+    ObjEvents {
+        blocks: Blocks::new(input),
+        events: VecDeque::new(),
+        finished: false,
+        parser: EventParser::new(options, (0, 0, 0)),
+    }
+}
+
+/// A statement of the `.obj` format, parsed by `ObjEvents`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ObjEvent {
+    /// Position vector of a vertex, specified by `v`.
+    Position(f32, f32, f32, f32),
+    /// Texture coordinates of a vertex, specified by `vt`.
+    TexCoord(f32, f32, f32),
+    /// Normal vector of a vertex, specified by `vn`.
+    Normal(f32, f32, f32),
+    /// Parametric vertex, specified by `vp`.
+    ParamVertex(f32, f32, f32),
+    /// Points, specified by `p`.
+    Points(Vec<Point>),
+    /// A line, specified by `l`.
+    Line(Line),
+    /// A polygon, specified by `f`.
+    Face(Polygon),
+    /// Names of the groups of the following elements, specified by `g`.
+    Group(Vec<String>),
+    /// Name of the object of the following elements, specified by `o`. `None` ends the object.
+    Object(Option<String>),
+    /// Material of the following elements, specified by `usemtl`.
+    UseMtl(String),
+    /// `.mtl` files, specified by `mtllib`.
+    MtlLib(Vec<String>),
+    /// Smoothing group of the following elements, specified by `s`. `None` turns it off.
+    Smoothing(Option<usize>),
+    /// Merging group of the following elements, specified by `mg`. `None` turns it off.
+    Merging(Option<usize>),
+    /// A statement of free-form geometry, which is not interpreted by the stream. Use
+    /// `parse_obj` to parse free-form curves and surfaces.
+    FreeForm(UnrecognizedStatement),
+    /// A statement which has been collected according to `ParseOptions`.
+    Unrecognized(UnrecognizedStatement),
+}
+
+/// Iterator over the events of a `.obj` file, created by `parse_obj_events`.
+#[derive(Debug)]
+pub struct ObjEvents<T> {
+    blocks: Blocks<T>,
+    /// Events of the last block which have not been yielded yet.
+    events: VecDeque<ObjResult<ObjEvent>>,
+    /// Whether the whole input has been read, or reading it has failed.
+    finished: bool,
+    parser: EventParser,
+}
+
+/// Parses statements into events, keeping track of the vertex data which indices refer to.
+#[derive(Debug)]
+pub(super) struct EventParser {
+    options: ParseOptions,
+    /// Number of vertex data parsed so far, which relative indices are resolved with.
+    positions: usize,
+    tex_coords: usize,
+    normals: usize,
+}
+
+impl EventParser {
+    /// Constructs a parser which continues after the given numbers of positions, texture
+    /// coordinates and normals.
+    pub(super) fn new(
+        options: &ParseOptions,
+        (positions, tex_coords, normals): (usize, usize, usize),
+    ) -> Self {
+        EventParser {
+            options: *options,
+            positions,
+            tex_coords,
+            normals,
+        }
+    }
+
+    /// Parses a statement. Returns `None` if the statement has been skipped.
+    pub(super) fn parse(
+        &mut self,
+        line: usize,
+        stmt: &str,
+        args: &[&str],
+        text: &str,
+    ) -> ObjResult<Option<ObjEvent>> {
+        let event = match stmt {
+            // Vertex data
+            "v" => {
+                let (x, y, z, w) = parse_position(args)?;
+                self.positions += 1;
+                ObjEvent::Position(x, y, z, w)
+            }
+            "vt" => {
+                let (u, v, w) = parse_tex_coord(args)?;
+                self.tex_coords += 1;
+                ObjEvent::TexCoord(u, v, w)
+            }
+            "vn" => {
+                let (x, y, z) = parse_normal(args)?;
+                self.normals += 1;
+                ObjEvent::Normal(x, y, z)
+            }
+            "vp" => {
+                let (u, v, w) = parse_param_vertex(args)?;
+                ObjEvent::ParamVertex(u, v, w)
+            }
+
+            // Elements
+            "p" => ObjEvent::Points(parse_points(args, self.positions)?),
+            "l" => ObjEvent::Line(parse_line(args, self.positions, self.tex_coords)?),
+            "fo" | "f" => ObjEvent::Face(parse_polygon(
+                args,
+                self.positions,
+                self.tex_coords,
+                self.normals,
+            )?),
+
+            // Free-form geometry
+            "cstype" | "deg" | "bmat" | "step" | "curv" | "curv2" | "surf" | "parm" | "trim"
+            | "hole" | "scrv" | "sp" | "end" | "con" | "ctech" | "stech" => {
+                ObjEvent::FreeForm(UnrecognizedStatement {
+                    line,
+                    statement: stmt.to_string(),
+                    arguments: args.iter().map(|arg| arg.to_string()).collect(),
+                })
+            }
+
+            // Grouping
+            "g" => match args {
+                [] => ObjEvent::Group(vec![String::from("default")]),
+                _ => ObjEvent::Group(args.iter().map(|name| name.to_string()).collect()),
+            },
+            "s" => ObjEvent::Smoothing(parse_group_number(args)?),
+            "mg" => ObjEvent::Merging(parse_group_number(args)?),
+            "o" => match args {
+                [] => ObjEvent::Object(None),
+                _ => ObjEvent::Object(Some(text.to_string())),
+            },
+
+            // Display / render attributes
+            "usemtl" => ObjEvent::UseMtl(parse_material(args)?),
+            "mtllib" => ObjEvent::MtlLib(args.iter().map(|path| path.to_string()).collect()),
+            // General statements, and the other display / render attributes
+            "call" | "csh" | "bevel" | "c_interp" | "d_interp" | "lod" | "maplib" | "usemap"
+            | "shadow_obj" | "trace_obj" => {
+                let mut unrecognized = Unrecognized::new(&self.options);
+                unrecognized.unsupported(line, stmt, args)?;
+                return Ok(unrecognized.statements.pop().map(ObjEvent::Unrecognized));
+            }
+
+            // Unexpected statement
+            _ => {
+                let mut unrecognized = Unrecognized::new(&self.options);
+                unrecognized.unknown(line, stmt, args)?;
+                return Ok(unrecognized.statements.pop().map(ObjEvent::Unrecognized));
+            }
+        };
+
+        Ok(Some(event))
+    }
+}
+
+impl<T: BufRead> ObjEvents<T> {
+    /// Lexes a block and parses its statements into `events`.
+    fn parse_block(&mut self) {
+        let (line, block) = match self.blocks.next_block() {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                self.finished = true;
+                return;
+            }
+            Err(e) => {
+                self.finished = true;
+                self.events.push_back(Err(e));
+                return;
+            }
+        };
+
+        let (parser, events) = (&mut self.parser, &mut self.events);
+        let result = lex_at(block, line, |line, stmt, args, text| {
+            match parser.parse(line, stmt, args, text) {
+                Ok(Some(event)) => events.push_back(Ok(event)),
+                Ok(None) => {}
+                Err(e) => events.push_back(Err(e.at(line, stmt))),
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.finished = true;
+            self.events.push_back(Err(e));
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for ObjEvents<T> {
+    type Item = ObjResult<ObjEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(event);
+            }
+            if self.finished {
+                return None;
+            }
+            self.parse_block();
+        }
+    }
+}
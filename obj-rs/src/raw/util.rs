@@ -1,4 +1,128 @@
+use crate::error::{ObjResult, make_error};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Parses &str into T, reporting the argument on failure.
+pub fn parse_arg<T>(arg: &str) -> ObjResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match arg.parse() {
+        Ok(val) => Ok(val),
+        Err(e) => make_error!(WrongTypeOfArguments, e, arg),
+    }
+}
+
 /// Parses &[&str] into Vec<f32>.
-pub fn parse_args(args: &[&str]) -> Result<Vec<f32>, std::num::ParseFloatError> {
-    args.iter().map(|arg| arg.parse()).collect()
+pub fn parse_args(args: &[&str]) -> ObjResult<Vec<f32>> {
+    args.iter().map(|arg| parse_float(arg)).collect()
+}
+
+/// Parses &[&str] into [f32; N], without allocation. `args` must have exactly `N` elements.
+pub fn parse_floats<const N: usize>(args: &[&str]) -> ObjResult<[f32; N]> {
+    if args.len() != N {
+        make_error!(WrongNumberOfArguments, format!("Expected {N} arguments"));
+    }
+
+    let mut floats = [0.0; N];
+    for (float, arg) in floats.iter_mut().zip(args) {
+        *float = parse_float(arg)?;
+    }
+    Ok(floats)
+}
+
+/// Parses &str into f32, reporting the argument on failure.
+///
+/// Besides what `f32::from_str` accepts (a leading `+`, `nan`, `inf` and exponents such as
+/// `1e5`), this accepts forms which some exporters emit: Fortran-style exponents such as
+/// `1.0D+03`, and the `1.#QNAN`, `1.#SNAN`, `1.#IND` and `1.#INF` forms of MSVC.
+pub fn parse_float(arg: &str) -> ObjResult<f32> {
+    if let Some(val) = parse_short_float(arg.as_bytes()) {
+        return Ok(val);
+    }
+
+    match arg.parse() {
+        Ok(val) => Ok(val),
+        Err(e) => match parse_unusual_float(arg) {
+            Some(val) => Ok(val),
+            None => make_error!(WrongTypeOfArguments, e, arg),
+        },
+    }
+}
+
+/// Parses decimals such as `-12.3456`, which is how vertex data is usually written, faster than
+/// `f32::from_str` does. Returns `None` for any other form, or if the result might not be exact.
+///
+/// The digits are read into an integer, which is divided by a power of ten afterwards. Both are
+/// exactly representable in `f32` as long as they are small enough, and then the division is
+/// correctly rounded.
+fn parse_short_float(bytes: &[u8]) -> Option<f32> {
+    const POWERS_OF_TEN: [f32; 11] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10];
+
+    let (negative, bytes) = match bytes {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        _ => (false, bytes),
+    };
+
+    let mut mantissa = 0u32;
+    let mut digits = 0;
+    let mut fraction = None;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'0'..=b'9' if digits < 9 => {
+                mantissa = mantissa * 10 + u32::from(b - b'0');
+                digits += 1;
+            }
+            b'.' if fraction.is_none() => fraction = Some(i + 1),
+            _ => return None,
+        }
+    }
+    if digits == 0 || mantissa > 1 << f32::MANTISSA_DIGITS {
+        return None;
+    }
+
+    let scale = fraction.map_or(0, |start| bytes.len() - start);
+    let val = mantissa as f32 / POWERS_OF_TEN.get(scale)?;
+    Some(if negative { -val } else { val })
+}
+
+/// Parses the forms of floats which `f32::from_str` does not accept.
+fn parse_unusual_float(arg: &str) -> Option<f32> {
+    // MSVC-style special values, such as `-1.#INF00`
+    if let Some((digits, special)) = arg.split_once(".#") {
+        let (negative, digits) = match digits.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, digits.strip_prefix('+').unwrap_or(digits)),
+        };
+        let end = special.find(|c: char| c.is_ascii_digit());
+        let (name, padding) = special.split_at(end.unwrap_or(special.len()));
+        if !digits.bytes().all(|b| b.is_ascii_digit()) || !padding.bytes().all(|b| b == b'0') {
+            return None;
+        }
+
+        return if ["QNAN", "SNAN", "IND"]
+            .iter()
+            .any(|nan| name.eq_ignore_ascii_case(nan))
+        {
+            Some(f32::NAN)
+        } else if name.eq_ignore_ascii_case("INF") {
+            Some(if negative {
+                f32::NEG_INFINITY
+            } else {
+                f32::INFINITY
+            })
+        } else {
+            None
+        };
+    }
+
+    // Fortran-style exponent, which is copied to the stack with `e` in place of `D`
+    let idx = arg.find(['D', 'd'])?;
+    let mut buffer = [0; 64];
+    let buffer = buffer.get_mut(..arg.len())?;
+    buffer.copy_from_slice(arg.as_bytes());
+    buffer[idx] = b'e';
+    str::from_utf8(buffer).ok()?.parse().ok()
 }
@@ -0,0 +1,620 @@
+//! Serializes the low-level representations back into text
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::Write;
+
+use crate::error::ObjResult;
+use crate::raw::material::{Material, MtlColor, MtlTextureMap, RawMtl};
+use crate::raw::object::{
+    Curve, Curve2, CurveTechnique, CurveType, FreeFormAttributes, Group, Line, Polygon, Range,
+    RawObj, Surface, SurfaceTechnique, TrimCurve,
+};
+
+/// Writes a `RawObj` in wavefront `.obj` format.
+///
+/// Parsing the output with `parse_obj` results in a `RawObj` which equals to `obj`, as long as
+/// `obj` is a result of `parse_obj` itself, except that adjacent ranges of a group may be merged
+/// into one. Unrecognized statements are not written.
+pub fn write_obj<W: Write>(obj: &RawObj, output: W) -> ObjResult<()> {
+    let mut writer = ObjWriter {
+        output,
+        obj,
+        state: State {
+            object: None,
+            groups: vec!["default"],
+            material: None,
+            smoothing: None,
+            merging: None,
+        },
+        name: None,
+        attributes: None,
+        curve_technique: None,
+        surface_technique: None,
+    };
+    writer.write()
+}
+
+/// Writes a `RawMtl` in wavefront `.mtl` format.
+///
+/// Materials are written in the order of their names, so that the same materials always result in
+/// the same output. Unrecognized statements are not written.
+pub fn write_mtl<W: Write>(mtl: &RawMtl, mut output: W) -> ObjResult<()> {
+    let mut materials = mtl.materials.iter().collect::<Vec<_>>();
+    materials.sort_unstable_by_key(|&(name, _)| name);
+
+    for (i, (name, material)) in materials.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(output)?;
+        }
+        write_material(name, material, &mut output)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a single material in wavefront `.mtl` format, starting with `newmtl`.
+pub fn write_material<W: Write>(name: &str, material: &Material, mut output: W) -> ObjResult<()> {
+    writeln!(output, "newmtl {}", name)?;
+
+    for (stmt, color) in material.colors() {
+        match color {
+            Some(MtlColor::Rgb(r, g, b)) => writeln!(output, "{} {} {} {}", stmt, r, g, b)?,
+            Some(MtlColor::Xyz(x, y, z)) => writeln!(output, "{} xyz {} {} {}", stmt, x, y, z)?,
+            Some(MtlColor::Spectral(file, 1.0)) => writeln!(output, "{} spectral {}", stmt, file)?,
+            Some(MtlColor::Spectral(file, factor)) => {
+                writeln!(output, "{} spectral {} {}", stmt, file, factor)?
+            }
+            None => {}
+        }
+    }
+
+    if let Some(illumination_model) = material.illumination_model {
+        writeln!(output, "illum {}", illumination_model)?;
+    }
+    let scalars = [
+        ("d", material.dissolve),
+        ("Ns", material.specular_exponent),
+        ("Ni", material.optical_density),
+        ("Pr", material.roughness),
+        ("Pm", material.metallic),
+        ("Ps", material.sheen),
+        ("Pc", material.clearcoat_thickness),
+        ("Pcr", material.clearcoat_roughness),
+        ("aniso", material.anisotropy),
+        ("anisor", material.anisotropy_rotation),
+        ("sharpness", material.sharpness),
+    ];
+    for (stmt, value) in scalars {
+        if let Some(value) = value {
+            writeln!(output, "{} {}", stmt, value)?;
+        }
+    }
+
+    if let Some(antialias_textures) = material.antialias_textures {
+        let value = if antialias_textures { "on" } else { "off" };
+        writeln!(output, "map_aat {}", value)?;
+    }
+    for (stmt, map) in material.texture_maps() {
+        if let Some(map) = map {
+            writeln!(output, "{} {}", stmt, texture_map(map))?;
+        }
+    }
+    for map in &material.reflection_maps {
+        writeln!(output, "refl {}", texture_map(map))?;
+    }
+
+    Ok(())
+}
+
+/// Formats the options which differ from the default values, followed by the filename.
+fn texture_map(map: &MtlTextureMap) -> String {
+    let default = MtlTextureMap::default();
+    let on_off = |value| if value { "on" } else { "off" };
+
+    let mut args = Vec::new();
+    if map.bump_multiplier != default.bump_multiplier {
+        args.push(format!("-bm {}", map.bump_multiplier));
+    }
+    if map.origin_offset != default.origin_offset {
+        args.push(format!("-o {}", join(map.origin_offset.iter())));
+    }
+    if map.scale != default.scale {
+        args.push(format!("-s {}", join(map.scale.iter())));
+    }
+    if map.turbulence != default.turbulence {
+        args.push(format!("-t {}", join(map.turbulence.iter())));
+    }
+    if map.resolution != default.resolution {
+        args.push(format!("-texres {}", map.resolution));
+    }
+    if map.clamping != default.clamping {
+        args.push(format!("-clamp {}", on_off(map.clamping)));
+    }
+    if map.base_gain != default.base_gain {
+        args.push(format!("-mm {}", join(map.base_gain.iter())));
+    }
+    if map.blend_u != default.blend_u {
+        args.push(format!("-blendu {}", on_off(map.blend_u)));
+    }
+    if map.blend_v != default.blend_v {
+        args.push(format!("-blendv {}", on_off(map.blend_v)));
+    }
+    if map.channel != default.channel {
+        args.push(format!("-imfchan {}", map.channel));
+    }
+    if let Some(reflection_type) = map.reflection_type {
+        args.push(format!("-type {}", reflection_type));
+    }
+    if map.boost != default.boost {
+        args.push(format!("-boost {}", map.boost));
+    }
+    if map.color_correction != default.color_correction {
+        args.push(format!("-cc {}", on_off(map.color_correction)));
+    }
+    for (option, values) in &map.unknown_options {
+        args.push(option.clone());
+        args.extend(values.iter().cloned());
+    }
+    args.push(map.file.clone());
+
+    args.join(" ")
+}
+
+/// Kind of the elements which can be grouped.
+#[derive(Copy, Clone)]
+enum Kind {
+    Point,
+    Line,
+    Polygon,
+    Curve,
+    Surface,
+}
+
+impl Kind {
+    const ALL: [Kind; 5] = [
+        Kind::Point,
+        Kind::Line,
+        Kind::Polygon,
+        Kind::Curve,
+        Kind::Surface,
+    ];
+
+    fn ranges(self, group: &Group) -> &[Range] {
+        match self {
+            Kind::Point => &group.points,
+            Kind::Line => &group.lines,
+            Kind::Polygon => &group.polygons,
+            Kind::Curve => &group.curves,
+            Kind::Surface => &group.surfaces,
+        }
+    }
+
+    fn len(self, obj: &RawObj) -> usize {
+        match self {
+            Kind::Point => obj.points.len(),
+            Kind::Line => obj.lines.len(),
+            Kind::Polygon => obj.polygons.len(),
+            Kind::Curve => obj.curves.len(),
+            Kind::Surface => obj.surfaces.len(),
+        }
+    }
+}
+
+/// Groups which an element belongs to.
+#[derive(Clone, PartialEq)]
+struct State<'a> {
+    object: Option<&'a str>,
+    groups: Vec<&'a str>,
+    material: Option<&'a str>,
+    smoothing: Option<usize>,
+    merging: Option<usize>,
+}
+
+struct ObjWriter<'a, W> {
+    output: W,
+    obj: &'a RawObj,
+    /// Groups which have been started so far.
+    state: State<'a>,
+    /// Name of the last object which has been written.
+    name: Option<&'a str>,
+    /// Free-form attributes which have been written so far.
+    attributes: Option<FreeFormAttributes>,
+    curve_technique: Option<CurveTechnique>,
+    surface_technique: Option<SurfaceTechnique>,
+}
+
+impl<'a, W: Write> ObjWriter<'a, W> {
+    fn write(&mut self) -> ObjResult<()> {
+        let obj = self.obj;
+
+        if !obj.material_libraries.is_empty() {
+            writeln!(self.output, "mtllib {}", obj.material_libraries.join(" "))?;
+        }
+
+        for &(x, y, z, w) in &obj.positions {
+            if w == 1.0 {
+                writeln!(self.output, "v {} {} {}", x, y, z)?;
+            } else {
+                writeln!(self.output, "v {} {} {} {}", x, y, z, w)?;
+            }
+        }
+        for &(u, v, w) in &obj.tex_coords {
+            match (v, w) {
+                (0.0, 0.0) => writeln!(self.output, "vt {}", u)?,
+                (_, 0.0) => writeln!(self.output, "vt {} {}", u, v)?,
+                _ => writeln!(self.output, "vt {} {} {}", u, v, w)?,
+            }
+        }
+        for &(x, y, z) in &obj.normals {
+            writeln!(self.output, "vn {} {} {}", x, y, z)?;
+        }
+        for &(u, v, w) in &obj.param_vertices {
+            writeln!(self.output, "vp {} {} {}", u, v, w)?;
+        }
+
+        // Trimming curves have to be declared before the surfaces which refer to them
+        for curve in &obj.curves2 {
+            self.write_curve2(curve)?;
+        }
+
+        // Elements without any material come first, since there is no way to go back to them
+        // once `usemtl` has been written.
+        let states = Kind::ALL.map(|kind| states(obj, kind));
+        let unmaterialized = states.each_ref().map(|states| {
+            states
+                .iter()
+                .take_while(|state| state.material.is_none())
+                .count()
+        });
+        for (kind, (states, &count)) in Kind::ALL.iter().zip(states.iter().zip(&unmaterialized)) {
+            for (index, state) in states.iter().enumerate().take(count) {
+                self.write_element(*kind, index, state)?;
+            }
+        }
+        for (kind, (states, &count)) in Kind::ALL.iter().zip(states.iter().zip(&unmaterialized)) {
+            for (index, state) in states.iter().enumerate().skip(count) {
+                self.write_element(*kind, index, state)?;
+            }
+        }
+
+        for connection in &obj.connections {
+            let (first, ref first_curve) = connection.first;
+            let (second, ref second_curve) = connection.second;
+            writeln!(
+                self.output,
+                "con {} {} {} {}",
+                first + 1,
+                trim_curves(std::slice::from_ref(first_curve)),
+                second + 1,
+                trim_curves(std::slice::from_ref(second_curve)),
+            )?;
+        }
+
+        // `o` only sets the name of the following elements, so the name of the last object can
+        // be written without affecting any of them.
+        if self.name != obj.name.as_deref() {
+            match obj.name {
+                Some(ref name) => writeln!(self.output, "o {}", name)?,
+                None => writeln!(self.output, "o")?,
+            }
+        }
+
+        // Likewise, materials which no element follows can be written last
+        let mut unused = obj
+            .material_names
+            .iter()
+            .filter(|name| !obj.meshes.contains_key(*name))
+            .collect::<Vec<_>>();
+        unused.sort();
+        for name in unused {
+            writeln!(self.output, "usemtl {}", name)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_element(&mut self, kind: Kind, index: usize, state: &State<'a>) -> ObjResult<()> {
+        self.write_state(state)?;
+
+        let obj = self.obj;
+        match kind {
+            Kind::Point => writeln!(self.output, "p {}", obj.points[index] + 1)?,
+            Kind::Line => match obj.lines[index] {
+                Line::P(ref vec) => {
+                    writeln!(self.output, "l {}", join(vec.iter().map(|p| p + 1)))?;
+                }
+                Line::PT(ref vec) => {
+                    let vertices = vec.iter().map(|(p, t)| format!("{}/{}", p + 1, t + 1));
+                    writeln!(self.output, "l {}", join(vertices))?;
+                }
+            },
+            Kind::Polygon => {
+                let vertices = match obj.polygons[index] {
+                    Polygon::P(ref vec) => join(vec.iter().map(|p| p + 1)),
+                    Polygon::PT(ref vec) => {
+                        join(vec.iter().map(|(p, t)| format!("{}/{}", p + 1, t + 1)))
+                    }
+                    Polygon::PN(ref vec) => {
+                        join(vec.iter().map(|(p, n)| format!("{}//{}", p + 1, n + 1)))
+                    }
+                    Polygon::PTN(ref vec) => join(
+                        vec.iter()
+                            .map(|(p, t, n)| format!("{}/{}/{}", p + 1, t + 1, n + 1)),
+                    ),
+                };
+                writeln!(self.output, "f {}", vertices)?;
+            }
+            Kind::Curve => self.write_curve(&obj.curves[index])?,
+            Kind::Surface => self.write_surface(&obj.surfaces[index])?,
+        }
+
+        Ok(())
+    }
+
+    /// Writes grouping statements which turn the current state into `state`.
+    fn write_state(&mut self, state: &State<'a>) -> ObjResult<()> {
+        if self.state.object != state.object {
+            match state.object {
+                Some(name) => writeln!(self.output, "o {}", name)?,
+                None => writeln!(self.output, "o")?,
+            }
+            self.name = state.object;
+        }
+        if self.state.groups != state.groups && !state.groups.is_empty() {
+            writeln!(self.output, "g {}", state.groups.join(" "))?;
+        }
+        // There is no way to go back to the elements without any material
+        if self.state.material != state.material
+            && let Some(material) = state.material
+        {
+            writeln!(self.output, "usemtl {}", material)?;
+        }
+        if self.state.smoothing != state.smoothing {
+            match state.smoothing {
+                Some(group) => writeln!(self.output, "s {}", group)?,
+                None => writeln!(self.output, "s off")?,
+            }
+        }
+        if self.state.merging != state.merging {
+            match state.merging {
+                Some(group) => writeln!(self.output, "mg {}", group)?,
+                None => writeln!(self.output, "mg off")?,
+            }
+        }
+
+        self.state = state.clone();
+        Ok(())
+    }
+
+    fn write_curve(&mut self, curve: &Curve) -> ObjResult<()> {
+        self.write_attributes(&curve.attributes)?;
+        if curve.technique.is_some() && curve.technique != self.curve_technique {
+            match curve.technique {
+                Some(CurveTechnique::Parametric { resolution }) => {
+                    writeln!(self.output, "ctech cparm {}", resolution)?
+                }
+                Some(CurveTechnique::Space { max_length }) => {
+                    writeln!(self.output, "ctech cspace {}", max_length)?
+                }
+                Some(CurveTechnique::Curvature {
+                    max_distance,
+                    max_angle,
+                }) => writeln!(self.output, "ctech curv {} {}", max_distance, max_angle)?,
+                None => {}
+            }
+            self.curve_technique = curve.technique;
+        }
+
+        let (start, end) = curve.range;
+        let vertices = join(curve.vertices.iter().map(|v| v + 1));
+        writeln!(self.output, "curv {} {} {}", start, end, vertices)?;
+        self.write_parameters("u", &curve.parameters)?;
+        self.write_special_points(&curve.special_points)?;
+        writeln!(self.output, "end")?;
+        Ok(())
+    }
+
+    fn write_curve2(&mut self, curve: &Curve2) -> ObjResult<()> {
+        self.write_attributes(&curve.attributes)?;
+        let vertices = join(curve.vertices.iter().map(|v| v + 1));
+        writeln!(self.output, "curv2 {}", vertices)?;
+        self.write_parameters("u", &curve.parameters)?;
+        self.write_special_points(&curve.special_points)?;
+        writeln!(self.output, "end")?;
+        Ok(())
+    }
+
+    fn write_surface(&mut self, surface: &Surface) -> ObjResult<()> {
+        self.write_attributes(&surface.attributes)?;
+        if surface.technique.is_some() && surface.technique != self.surface_technique {
+            match surface.technique {
+                Some(SurfaceTechnique::ParametricA {
+                    resolution_u,
+                    resolution_v,
+                }) => writeln!(
+                    self.output,
+                    "stech cparma {} {}",
+                    resolution_u, resolution_v
+                )?,
+                Some(SurfaceTechnique::ParametricB { resolution }) => {
+                    writeln!(self.output, "stech cparmb {}", resolution)?
+                }
+                Some(SurfaceTechnique::Space { max_length }) => {
+                    writeln!(self.output, "stech cspace {}", max_length)?
+                }
+                Some(SurfaceTechnique::Curvature {
+                    max_distance,
+                    max_angle,
+                }) => writeln!(self.output, "stech curv {} {}", max_distance, max_angle)?,
+                None => {}
+            }
+            self.surface_technique = surface.technique;
+        }
+
+        let vertices = surface.vertices.iter().map(|&(p, t, n)| match (t, n) {
+            (None, None) => format!("{}", p + 1),
+            (Some(t), None) => format!("{}/{}", p + 1, t + 1),
+            (None, Some(n)) => format!("{}//{}", p + 1, n + 1),
+            (Some(t), Some(n)) => format!("{}/{}/{}", p + 1, t + 1, n + 1),
+        });
+        let ((s0, s1), (t0, t1)) = (surface.range_u, surface.range_v);
+        writeln!(
+            self.output,
+            "surf {} {} {} {} {}",
+            s0,
+            s1,
+            t0,
+            t1,
+            join(vertices)
+        )?;
+        self.write_parameters("u", &surface.parameters_u)?;
+        self.write_parameters("v", &surface.parameters_v)?;
+        for trim in &surface.trims {
+            writeln!(self.output, "trim {}", trim_curves(trim))?;
+        }
+        for hole in &surface.holes {
+            writeln!(self.output, "hole {}", trim_curves(hole))?;
+        }
+        for special_curve in &surface.special_curves {
+            writeln!(self.output, "scrv {}", trim_curves(special_curve))?;
+        }
+        self.write_special_points(&surface.special_points)?;
+        writeln!(self.output, "end")?;
+        Ok(())
+    }
+
+    /// Writes free-form attribute statements which differ from the ones written so far.
+    fn write_attributes(&mut self, attributes: &FreeFormAttributes) -> ObjResult<()> {
+        // `cstype` and `deg` have no default values, so they are always written first
+        let first = self.attributes.is_none();
+        let written = self.attributes.take().unwrap_or(FreeFormAttributes {
+            rational: attributes.rational,
+            curve_type: attributes.curve_type,
+            degree: attributes.degree,
+            basis_matrix: (Vec::new(), Vec::new()),
+            step: (1, 1),
+        });
+
+        if first
+            || (written.rational, written.curve_type)
+                != (attributes.rational, attributes.curve_type)
+        {
+            let curve_type = match attributes.curve_type {
+                CurveType::BasisMatrix => "bmatrix",
+                CurveType::Bezier => "bezier",
+                CurveType::BSpline => "bspline",
+                CurveType::Cardinal => "cardinal",
+                CurveType::Taylor => "taylor",
+            };
+            if attributes.rational {
+                writeln!(self.output, "cstype rat {}", curve_type)?;
+            } else {
+                writeln!(self.output, "cstype {}", curve_type)?;
+            }
+        }
+        if first || written.degree != attributes.degree {
+            match attributes.degree {
+                (u, 0) => writeln!(self.output, "deg {}", u)?,
+                (u, v) => writeln!(self.output, "deg {} {}", u, v)?,
+            }
+        }
+        if written.basis_matrix.0 != attributes.basis_matrix.0 {
+            let matrix = join(attributes.basis_matrix.0.iter());
+            writeln!(self.output, "bmat u {}", matrix)?;
+        }
+        if written.basis_matrix.1 != attributes.basis_matrix.1 {
+            let matrix = join(attributes.basis_matrix.1.iter());
+            writeln!(self.output, "bmat v {}", matrix)?;
+        }
+        if written.step != attributes.step {
+            writeln!(
+                self.output,
+                "step {} {}",
+                attributes.step.0, attributes.step.1
+            )?;
+        }
+
+        self.attributes = Some(attributes.clone());
+        Ok(())
+    }
+
+    fn write_parameters(&mut self, direction: &str, parameters: &[f32]) -> ObjResult<()> {
+        if !parameters.is_empty() {
+            writeln!(
+                self.output,
+                "parm {} {}",
+                direction,
+                join(parameters.iter())
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_special_points(&mut self, special_points: &[usize]) -> ObjResult<()> {
+        if !special_points.is_empty() {
+            let points = join(special_points.iter().map(|vp| vp + 1));
+            writeln!(self.output, "sp {}", points)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds out which groups each element of the given kind belongs to.
+fn states(obj: &RawObj, kind: Kind) -> Vec<State<'_>> {
+    let len = kind.len(obj);
+    let objects = membership(&obj.objects, kind, len);
+    let groups = membership(&obj.groups, kind, len);
+    let materials = membership(&obj.meshes, kind, len);
+    let smoothing = membership(&obj.smoothing_groups, kind, len);
+    let merging = membership(&obj.merging_groups, kind, len);
+
+    (0..len)
+        .map(|i| {
+            let mut groups = groups[i]
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>();
+            groups.sort_unstable();
+            State {
+                object: objects[i].first().map(|name| name.as_str()),
+                groups,
+                // Elements before the first `usemtl` belong to the material with empty name
+                material: materials[i]
+                    .first()
+                    .map(|name| name.as_str())
+                    .filter(|name| !name.is_empty()),
+                smoothing: smoothing[i].first().map(|&&group| group),
+                merging: merging[i].first().map(|&&group| group),
+            }
+        })
+        .collect()
+}
+
+/// Lists the keys of the groups which each element of the given kind belongs to.
+fn membership<K: Eq + Hash>(groups: &HashMap<K, Group>, kind: Kind, len: usize) -> Vec<Vec<&K>> {
+    let mut result = vec![Vec::new(); len];
+    for (key, group) in groups {
+        for range in kind.ranges(group) {
+            for keys in &mut result[range.start..range.end.min(len)] {
+                keys.push(key);
+            }
+        }
+    }
+    result
+}
+
+fn trim_curves(curves: &[TrimCurve]) -> String {
+    join(
+        curves
+            .iter()
+            .map(|curve| format!("{} {} {}", curve.start, curve.end, curve.curve + 1)),
+    )
+}
+
+fn join<T: ToString>(items: impl Iterator<Item = T>) -> String {
+    items
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
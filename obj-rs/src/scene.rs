@@ -0,0 +1,146 @@
+//! Multi-mesh loading, which keeps track of objects, groups and materials
+
+use crate::error::make_error;
+use crate::raw::object::{Polygon, RawObj};
+use crate::{FromRawVertex, LoadOptions, ObjResult, Vertex, raw};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Load a wavefront OBJ file into a `Scene`, with one `Model` per object, group and material
+/// combination.
+pub fn load_scene<V: FromRawVertex<I>, T: BufRead, I>(input: T) -> ObjResult<Scene<V, I>> {
+    load_scene_with_options(input, &LoadOptions::default())
+}
+
+/// Load a wavefront OBJ file into a `Scene`, with the given options.
+pub fn load_scene_with_options<V: FromRawVertex<I>, T: BufRead, I>(
+    input: T,
+    options: &LoadOptions,
+) -> ObjResult<Scene<V, I>> {
+    let raw = raw::parse_obj_with_options(input, &options.parse)?;
+    Scene::with_options(raw, options)
+}
+
+/// 3D model loaded from wavefront OBJ, split into several models.
+///
+/// Every model shares the same vertex and index buffer, and owns a contiguous range of the index
+/// buffer. This allows issuing one draw call per model.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Scene<V = Vertex, I = u16> {
+    /// Vertex buffer.
+    pub vertices: Vec<V>,
+    /// Index buffer.
+    pub indices: Vec<I>,
+    /// Models of the scene, in the order of their first appearance in the file.
+    pub models: Vec<Model>,
+}
+
+/// Part of a `Scene` whose polygons belong to the same object, groups and material.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Model {
+    /// Name of the object, given by `o`, or `None` for polygons which precede every `o` or
+    /// follow an `o` without a name.
+    pub object: Option<String>,
+    /// Names of the groups, sorted.
+    pub groups: Vec<String>,
+    /// Name of the material, given by `usemtl`.
+    pub material: Option<String>,
+    /// Range of the index buffer of the `Scene`, which the model is drawn with.
+    pub indices: Range<usize>,
+}
+
+impl<V: FromRawVertex<I>, I> Scene<V, I> {
+    /// Create `Scene` from `RawObj` object.
+    pub fn new(raw: RawObj) -> ObjResult<Self> {
+        Scene::with_options(raw, &LoadOptions::default())
+    }
+
+    /// Create `Scene` from `RawObj` object, with the given options.
+    ///
+    /// The `RawObj` goes through the same steps as `Obj::with_options`.
+    pub fn with_options(mut raw: RawObj, options: &LoadOptions) -> ObjResult<Self> {
+        options.prepare(&mut raw)?;
+
+        // Find out which object, groups and material each polygon belongs to
+        let mut objects = vec![None; raw.polygons.len()];
+        for (name, object) in &raw.objects {
+            for range in &object.polygons {
+                objects[range.start..range.end].fill(Some(name.clone()));
+            }
+        }
+        let mut groups = vec![Vec::new(); raw.polygons.len()];
+        for (name, group) in &raw.groups {
+            for range in &group.polygons {
+                for polygon in &mut groups[range.start..range.end] {
+                    polygon.push(name.clone());
+                }
+            }
+        }
+        let mut materials = vec![None; raw.polygons.len()];
+        for (name, mesh) in &raw.meshes {
+            if name.is_empty() {
+                continue;
+            }
+            for range in &mesh.polygons {
+                materials[range.start..range.end].fill(Some(name.clone()));
+            }
+        }
+
+        // Gather polygons of each model, in the order of their first appearance
+        let mut models = Vec::<(Model, Vec<Polygon>)>::new();
+        let mut lookup = HashMap::new();
+        let keys = objects.into_iter().zip(groups).zip(materials);
+        for (polygon, ((object, mut groups), material)) in raw.polygons.into_iter().zip(keys) {
+            groups.sort();
+            let key = (object, groups, material);
+            let index = *lookup.entry(key.clone()).or_insert_with(|| {
+                let (object, groups, material) = key;
+                let model = Model {
+                    object,
+                    groups,
+                    material,
+                    indices: 0..0,
+                };
+                models.push((model, Vec::new()));
+                models.len() - 1
+            });
+            models[index].1.push(polygon);
+        }
+
+        // Every polygon is a triangle after a successful `process`, so each model owns three
+        // indices per polygon
+        let mut start = 0;
+        let mut polygons = Vec::new();
+        let models = models
+            .into_iter()
+            .map(|(mut model, mut vec)| {
+                model.indices = start * 3..(start + vec.len()) * 3;
+                start += vec.len();
+                polygons.append(&mut vec);
+                model
+            })
+            .collect();
+        let count = polygons.len();
+
+        let (vertices, indices) =
+            FromRawVertex::process(raw.positions, raw.normals, raw.tex_coords, polygons)?;
+        if indices.len() != count * 3 {
+            make_error!(
+                UntriangulatedModel,
+                "Expected the vertex type to emit three indices per polygon"
+            );
+        }
+
+        Ok(Scene {
+            vertices,
+            indices,
+            models,
+        })
+    }
+}
@@ -0,0 +1,176 @@
+//! Computes tangent spaces the way MikkTSpace does
+
+use crate::error::{index_out_of_range, make_error};
+use crate::raw::object::Polygon;
+use crate::vector::{add, cross, dot, normalize, safe_normalize, scale, sub};
+use crate::{ObjResult, TangentVertex};
+use num_traits::FromPrimitive;
+use std::collections::hash_map::{Entry, HashMap};
+
+/// Corner of a triangle, identified by the bits of its position, normal and texture coordinates,
+/// and whether the texture mapping of the triangle preserves its orientation.
+///
+/// Like MikkTSpace, corners are welded by value, so vertices which an exporter duplicated still
+/// share their tangent. MikkTSpace never shares a tangent between triangles of different
+/// handedness, so such corners become separate vertices.
+type Key = ([u32; 3], [u32; 3], [u32; 3], bool);
+
+/// Builds vertex and index buffer of `TangentVertex`.
+///
+/// Tangents of a vertex are averaged over every triangle sharing it, weighted by the angle of the
+/// triangle at the vertex, after projecting them onto the plane perpendicular to the normal.
+/// Triangles without texture area take the tangent space of their neighbors.
+pub(crate) fn process<I: FromPrimitive + Copy>(
+    positions: Vec<(f32, f32, f32, f32)>,
+    normals: Vec<(f32, f32, f32)>,
+    tex_coords: Vec<(f32, f32, f32)>,
+    polygons: Vec<Polygon>,
+) -> ObjResult<(Vec<TangentVertex>, Vec<I>)> {
+    let mut triangles = Vec::with_capacity(polygons.len());
+    for polygon in polygons {
+        match polygon {
+            Polygon::P(_) => make_error!(
+                InsufficientData,
+                "Tried to extract normal and texture data which are not contained in the model"
+            ),
+            Polygon::PT(_) => make_error!(
+                InsufficientData,
+                "Tried to extract normal data which are not contained in the model"
+            ),
+            Polygon::PN(_) => make_error!(
+                InsufficientData,
+                "Tried to extract texture data which are not contained in the model"
+            ),
+            Polygon::PTN(vec) if vec.len() == 3 => triangles.push([vec[0], vec[1], vec[2]]),
+            _ => make_error!(
+                UntriangulatedModel,
+                "Model should be triangulated first to be loaded properly"
+            ),
+        }
+    }
+
+    let position = |i: usize| {
+        let (x, y, z, _) = positions[i];
+        [x, y, z]
+    };
+    let normal = |i: usize| {
+        let (x, y, z) = normals[i];
+        [x, y, z]
+    };
+    let texture = |i: usize| {
+        let (u, v, _) = tex_coords[i];
+        [u, v]
+    };
+    let value = |(pi, ti, ni): (usize, usize, usize)| {
+        let (u, v, w) = tex_coords[ti];
+        (bits(position(pi)), bits(normal(ni)), bits([u, v, w]))
+    };
+
+    // Tangent space of every triangle, or `None` if it has no texture area
+    let spaces = triangles
+        .iter()
+        .map(|triangle| {
+            let [p0, p1, p2] = triangle.map(|(pi, _, _)| position(pi));
+            let [t0, t1, t2] = triangle.map(|(_, ti, _)| texture(ti));
+            let (d1, d2) = (sub(p1, p0), sub(p2, p0));
+            let (t10, t20) = (
+                [t1[0] - t0[0], t1[1] - t0[1]],
+                [t2[0] - t0[0], t2[1] - t0[1]],
+            );
+
+            let area = t10[0] * t20[1] - t10[1] * t20[0];
+            if area.abs() <= f32::MIN_POSITIVE {
+                return None;
+            }
+            let s = scale(sub(scale(d1, t20[1]), scale(d2, t10[1])), area.signum());
+            Some((s, area > 0.0))
+        })
+        .collect::<Vec<_>>();
+
+    let mut vb = Vec::with_capacity(triangles.len() * 3);
+    let mut ib = Vec::with_capacity(triangles.len() * 3);
+    let mut sums = Vec::with_capacity(triangles.len() * 3);
+    let mut cache = HashMap::<Key, usize>::new();
+
+    // Accumulate the tangents of triangles with texture area first, so that the others can find
+    // out which handedness their vertices have.
+    let mut order = (0..triangles.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| spaces[i].is_none());
+
+    let mut corners = vec![[0; 3]; triangles.len()];
+    for i in order {
+        let triangle = triangles[i];
+        for k in 0..3 {
+            let (pi, ti, ni) = triangle[k];
+            let (p, n, t) = value(triangle[k]);
+            let key = match spaces[i] {
+                Some((_, orientation)) => (p, n, t, orientation),
+                None if cache.contains_key(&(p, n, t, false)) => (p, n, t, false),
+                None => (p, n, t, true),
+            };
+            let index = match cache.entry(key) {
+                Entry::Vacant(entry) => {
+                    let t = tex_coords[ti];
+                    vb.push(TangentVertex {
+                        position: position(pi),
+                        normal: normal(ni),
+                        texture: [t.0, t.1, t.2],
+                        tangent: [0.0, 0.0, 0.0, if key.3 { 1.0 } else { -1.0 }],
+                    });
+                    sums.push([0.0; 3]);
+                    *entry.insert(vb.len() - 1)
+                }
+                Entry::Occupied(entry) => *entry.get(),
+            };
+            corners[i][k] = index;
+
+            let Some((s, _)) = spaces[i] else { continue };
+            let n = safe_normalize(normal(ni));
+            let edge = |other: usize| {
+                let (pj, _, _) = triangle[other];
+                safe_normalize(project(sub(position(pj), position(pi)), n))
+            };
+            let angle = dot(edge((k + 1) % 3), edge((k + 2) % 3))
+                .clamp(-1.0, 1.0)
+                .acos();
+            sums[index] = add(sums[index], scale(safe_normalize(project(s, n)), angle));
+        }
+    }
+
+    for corner in corners.into_iter().flatten() {
+        ib.push(match I::from_usize(corner) {
+            Some(val) => val,
+            None => return index_out_of_range::<_, I>(corner),
+        });
+    }
+
+    for (vertex, sum) in vb.iter_mut().zip(sums) {
+        let n = safe_normalize(vertex.normal);
+        let tangent = if dot(sum, sum) > 0.0 {
+            normalize(sum)
+        } else {
+            // No triangle has texture area around this vertex, so any perpendicular direction
+            // does the job.
+            let axis = if n[0].abs() > 0.9 {
+                [0.0, 1.0, 0.0]
+            } else {
+                [1.0, 0.0, 0.0]
+            };
+            safe_normalize(cross(axis, n))
+        };
+        vertex.tangent = [tangent[0], tangent[1], tangent[2], vertex.tangent[3]];
+    }
+
+    Ok((vb, ib))
+}
+
+/// Returns the bits of a vector, with negative zeros turned into positive ones so that they
+/// compare equal.
+fn bits(a: [f32; 3]) -> [u32; 3] {
+    a.map(|x| (x + 0.0).to_bits())
+}
+
+/// Projects `a` onto the plane perpendicular to the unit vector `n`.
+fn project(a: [f32; 3], n: [f32; 3]) -> [f32; 3] {
+    sub(a, scale(n, dot(a, n)))
+}
@@ -0,0 +1,212 @@
+//! Splits polygons into triangles
+
+use crate::raw::object::{Polygon, Range, RawObj};
+use crate::vector::{cross, dot, normalize};
+
+/// Method of splitting polygons which are not triangles.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Triangulation {
+    /// Do not triangulate. Polygons which are not triangles are rejected with
+    /// `UntriangulatedModel`.
+    #[default]
+    Disabled,
+    /// Connect the first vertex of a polygon to every other edge. This is fast, but produces
+    /// wrong results for concave polygons.
+    Fan,
+    /// Clip ears of a polygon after projecting it onto its best-fit plane. This handles concave
+    /// and non-planar polygons.
+    EarClipping,
+}
+
+/// Replaces every polygon of `raw` with triangles, and updates the polygon ranges of every group
+/// accordingly.
+pub(crate) fn triangulate(raw: &mut RawObj, method: Triangulation) {
+    if method == Triangulation::Disabled || raw.polygons.iter().all(|p| len(p) == 3) {
+        return;
+    }
+
+    // `offsets[i]` is the index of the first triangle made from the `i`th polygon
+    let mut offsets = Vec::with_capacity(raw.polygons.len() + 1);
+    let mut triangles = Vec::with_capacity(raw.polygons.len() * 2);
+    for polygon in raw.polygons.drain(..) {
+        offsets.push(triangles.len());
+        if len(&polygon) <= 3 {
+            triangles.push(polygon);
+            continue;
+        }
+
+        let corners = match method {
+            Triangulation::EarClipping => ear_clipping(&positions(&raw.positions, &polygon)),
+            _ => fan(len(&polygon)),
+        };
+        triangles.extend(split(&polygon, &corners));
+    }
+    offsets.push(triangles.len());
+    raw.polygons = triangles;
+
+    raw.for_each_group(|group| {
+        for range in &mut group.polygons {
+            *range = Range {
+                start: offsets[range.start],
+                end: offsets[range.end],
+            };
+        }
+    });
+}
+
+pub(crate) fn len(polygon: &Polygon) -> usize {
+    match polygon {
+        Polygon::P(vec) => vec.len(),
+        Polygon::PT(vec) | Polygon::PN(vec) => vec.len(),
+        Polygon::PTN(vec) => vec.len(),
+    }
+}
+
+pub(crate) fn positions(positions: &[(f32, f32, f32, f32)], polygon: &Polygon) -> Vec<[f32; 3]> {
+    let position = |i: usize| {
+        let (x, y, z, _) = positions[i];
+        [x, y, z]
+    };
+
+    match polygon {
+        Polygon::P(vec) => vec.iter().map(|&p| position(p)).collect(),
+        Polygon::PT(vec) | Polygon::PN(vec) => vec.iter().map(|&(p, _)| position(p)).collect(),
+        Polygon::PTN(vec) => vec.iter().map(|&(p, _, _)| position(p)).collect(),
+    }
+}
+
+/// Builds triangles out of a polygon, with the given corner indices.
+fn split(polygon: &Polygon, triangles: &[[usize; 3]]) -> Vec<Polygon> {
+    fn pick<T: Copy>(vec: &[T], corners: &[usize; 3]) -> Vec<T> {
+        corners.iter().map(|&i| vec[i]).collect()
+    }
+
+    triangles
+        .iter()
+        .map(|corners| match polygon {
+            Polygon::P(vec) => Polygon::P(pick(vec, corners)),
+            Polygon::PT(vec) => Polygon::PT(pick(vec, corners)),
+            Polygon::PN(vec) => Polygon::PN(pick(vec, corners)),
+            Polygon::PTN(vec) => Polygon::PTN(pick(vec, corners)),
+        })
+        .collect()
+}
+
+fn fan(len: usize) -> Vec<[usize; 3]> {
+    (1..len - 1).map(|i| [0, i, i + 1]).collect()
+}
+
+/// Triangulates a polygon by ear clipping. Returns the corner indices of each triangle, in the
+/// winding order of the polygon.
+fn ear_clipping(points: &[[f32; 3]]) -> Vec<[usize; 3]> {
+    // Project the polygon onto the plane computed by Newell's method, where the polygon winds
+    // counterclockwise.
+    let normal = normalize(newell_normal(points));
+    let axis = if normal[0].abs() > 0.9 {
+        [0.0, 1.0, 0.0]
+    } else {
+        [1.0, 0.0, 0.0]
+    };
+    let u = normalize(cross(axis, normal));
+    let v = cross(normal, u);
+    if u.iter().chain(&v).any(|x| !x.is_finite()) {
+        // Degenerated polygon without any area
+        return fan(points.len());
+    }
+    let projected = points
+        .iter()
+        .map(|&p| (dot(p, u), dot(p, v)))
+        .collect::<Vec<_>>();
+
+    let mut remaining = (0..points.len()).collect::<Vec<_>>();
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let corners = |i: usize| {
+            [
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            ]
+        };
+
+        let is_ear = |i: usize| {
+            let [a, b, c] = corners(i).map(|k| projected[k]);
+            if orientation(a, b, c) <= 0.0 {
+                return false;
+            }
+            remaining
+                .iter()
+                .filter(|&&k| !corners(i).contains(&k))
+                .all(|&k| !is_in_triangle(projected[k], a, b, c))
+        };
+
+        // Fall back to the most convex corner if numerical errors leave no ear at all
+        let ear = (0..n).find(|&i| is_ear(i)).unwrap_or_else(|| {
+            (0..n)
+                .max_by(|&i, &j| {
+                    let area = |i| {
+                        let [a, b, c] = corners(i).map(|k| projected[k]);
+                        orientation(a, b, c)
+                    };
+                    area(i).total_cmp(&area(j))
+                })
+                .unwrap_or(0)
+        });
+
+        triangles.push(corners(ear));
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    triangles
+}
+
+/// Computes the normal of the best-fit plane of a polygon. Its length is twice the area of the
+/// polygon.
+pub(crate) fn newell_normal(points: &[[f32; 3]]) -> [f32; 3] {
+    let mut normal = [0.0; 3];
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        normal[0] += (p[1] - q[1]) * (p[2] + q[2]);
+        normal[1] += (p[2] - q[2]) * (p[0] + q[0]);
+        normal[2] += (p[0] - q[0]) * (p[1] + q[1]);
+    }
+    normal
+}
+
+/// Returns twice the signed area of a 2D triangle, which is positive if it is counterclockwise.
+fn orientation(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Tests whether `p` is inside of, or on the boundary of, a counterclockwise triangle.
+fn is_in_triangle(p: (f32, f32), a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    orientation(a, b, p) >= 0.0 && orientation(b, c, p) >= 0.0 && orientation(c, a, p) >= 0.0
+}
+
+#[test]
+fn test_ear_clipping() {
+    // A concave "L" shape, where fan triangulation from the first vertex would leave the polygon
+    let points = [
+        [2.0, 0.0, 0.0],
+        [2.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 2.0, 0.0],
+        [0.0, 2.0, 0.0],
+        [0.0, 0.0, 0.0],
+    ];
+    let triangles = ear_clipping(&points);
+    assert_eq!(triangles.len(), 4);
+
+    // Every triangle keeps the winding order of the polygon
+    let area = |[a, b, c]: [usize; 3]| {
+        let (p, q, r) = (points[a], points[b], points[c]);
+        (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])
+    };
+    assert!(triangles.iter().all(|&t| area(t) > 0.0));
+
+    // The triangles cover the whole polygon, whose area is 3
+    let total: f32 = triangles.iter().map(|&t| area(t) / 2.0).sum();
+    assert!((total - 3.0).abs() < 1e-6);
+}
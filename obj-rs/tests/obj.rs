@@ -1,4 +1,7 @@
-use obj::{Obj, ObjResult, Position, TexturedVertex, Vertex, load_obj};
+use obj::{
+    LoadOptions, NormalGeneration, Obj, ObjResult, Position, TangentVertex, TexturedVertex,
+    Triangulation, Vertex, load_obj, load_obj_with_options,
+};
 use std::fs::File;
 use std::io::{BufReader, Error};
 
@@ -125,3 +128,204 @@ fn textured_cube() -> ObjResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn triangulation() -> ObjResult<()> {
+    let options = |triangulation| LoadOptions {
+        triangulation,
+        ..LoadOptions::default()
+    };
+
+    assert!(load_obj::<Position, _, u16>(fixture("cube.obj")?).is_err());
+
+    let obj: Obj<Position> =
+        load_obj_with_options(fixture("cube.obj")?, &options(Triangulation::Fan))?;
+    assert_eq!(obj.vertices.len(), 8);
+    assert_eq!(obj.indices.len(), 36);
+    assert_eq!(obj.indices[..6], [0, 1, 2, 0, 2, 3]);
+
+    // A concave "L" shape, where fan triangulation would produce a triangle outside of it
+    let input = b"
+v 2 0 0
+v 2 1 0
+v 1 1 0
+v 1 2 0
+v 0 2 0
+v 0 0 0
+vn 0 0 1
+f 1//1 2//1 3//1 4//1 5//1 6//1
+";
+    let obj: Obj<Vertex> = load_obj_with_options(&input[..], &options(Triangulation::EarClipping))?;
+    assert_eq!(obj.indices.len(), 12);
+    let area: f32 = obj
+        .indices
+        .chunks(3)
+        .map(|t| {
+            let [p, q, r] = [0, 1, 2].map(|i| obj.vertices[t[i] as usize].position);
+            (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])
+        })
+        .inspect(|&a| assert!(a > 0.0))
+        .sum();
+    assert_eq!(area / 2.0, 3.0);
+
+    Ok(())
+}
+
+#[test]
+fn normal_generation() -> ObjResult<()> {
+    let options = |normals| LoadOptions {
+        normals,
+        triangulation: Triangulation::Fan,
+        ..LoadOptions::default()
+    };
+
+    assert!(load_obj::<TexturedVertex, _, u16>(fixture("cube.obj")?).is_err());
+
+    // Every face of the cube is in `s off`, so it is flat shaded even with smoothing
+    let obj: Obj<TexturedVertex> = load_obj_with_options(
+        fixture("cube.obj")?,
+        &options(NormalGeneration::AreaWeighted),
+    )?;
+    assert_eq!(obj.vertices.len(), 24);
+    assert_eq!(obj.indices.len(), 36);
+    let approx = |a: [f32; 3], b: [f32; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-6);
+    assert!(approx(obj.vertices[0].normal, [0.0, -1.0, 0.0]));
+    assert!(approx(obj.vertices[4].normal, [0.0, 1.0, 0.0]));
+
+    // Two faces of a smoothing group, bent by 90 degrees along their shared edge
+    let input = b"
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 1 -1
+v 0 0 -1
+s 1
+f 1 2 3 4
+f 1 4 5 6
+";
+    let obj: Obj<Vertex> = load_obj_with_options(&input[..], &options(NormalGeneration::Flat))?;
+    assert_eq!(obj.vertices.len(), 8);
+    assert_eq!(obj.vertices[0].normal, [0.0, 0.0, 1.0]);
+    assert_eq!(obj.vertices[4].normal, [-1.0, 0.0, 0.0]);
+
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    for method in [
+        NormalGeneration::AreaWeighted,
+        NormalGeneration::AngleWeighted,
+    ] {
+        let obj: Obj<Vertex> = load_obj_with_options(&input[..], &options(method))?;
+        assert_eq!(obj.vertices.len(), 6);
+        assert_eq!(obj.vertices[0].normal, [-half, 0.0, half]);
+        assert_eq!(obj.vertices[1].normal, [0.0, 0.0, 1.0]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tangent_vertex() -> ObjResult<()> {
+    let obj: Obj<TangentVertex> = load_obj(fixture("textured-cube.obj")?)?;
+
+    assert_eq!(obj.vertices.len(), 24);
+    assert_eq!(obj.indices.len(), 36);
+    assert_eq!(
+        obj.vertices[0],
+        TangentVertex {
+            position: [-0.5, -0.5, 0.5],
+            normal: [0.0, 0.0, 1.0],
+            texture: [0.0, 0.0, 0.0],
+            tangent: [1.0, 0.0, 0.0, 1.0],
+        }
+    );
+    // The top face is mapped the same way, with U along the x axis
+    assert_eq!(obj.vertices[4].tangent, [1.0, 0.0, 0.0, 1.0]);
+
+    // A quad whose right half has a mirrored texture, which flips the handedness of its tangent
+    // space. The shared edge is split into separate vertices.
+    let input = b"
+v 0 0 0
+v 1 0 0
+v 2 0 0
+v 0 1 0
+v 1 1 0
+v 2 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vt 1 1
+vn 0 0 1
+f 1/1/1 2/2/1 5/4/1
+f 1/1/1 5/4/1 4/3/1
+f 2/2/1 3/1/1 6/3/1
+f 2/2/1 6/3/1 5/4/1
+";
+    let obj: Obj<TangentVertex> = load_obj(&input[..])?;
+    assert_eq!(obj.vertices.len(), 8);
+    assert_eq!(obj.vertices[0].tangent, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(obj.vertices[4].tangent, [-1.0, 0.0, 0.0, -1.0]);
+
+    // Two triangles whose shared corners are duplicated with equal values, as some exporters
+    // write them. The corners are welded, so they share a tangent.
+    let input = b"
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vt 1 2
+vt 1 0
+vt 0 1
+vn 0 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+f 5/5/2 4/4/2 6/6/2
+";
+    let indexed = b"
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vt 1 2
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+f 2/2/1 4/4/1 3/3/1
+";
+    let obj: Obj<TangentVertex> = load_obj(&input[..])?;
+    assert_eq!(obj.vertices.len(), 4);
+    assert_eq!(obj, load_obj(&indexed[..])?);
+    assert_ne!(obj.vertices[1].tangent, [1.0, 0.0, 0.0, 1.0]);
+
+    Ok(())
+}
+
+#[test]
+fn write() -> ObjResult<()> {
+    let obj: Obj<TexturedVertex> = load_obj(fixture("textured-cube.obj")?)?;
+    let mut output = Vec::new();
+    obj.write(&mut output)?;
+    assert_eq!(load_obj::<TexturedVertex, _, u16>(&output[..])?, obj);
+
+    let obj: Obj<Position, u32> = load_obj(fixture("dome.obj")?)?;
+    let raw = obj.to_raw()?;
+    assert_eq!(raw.name, Some("Dome".to_string()));
+    assert_eq!(raw.positions.len(), obj.vertices.len());
+    assert_eq!(raw.polygons.len() * 3, obj.indices.len());
+
+    // Vertices which differ only in their normals share the same position
+    let obj: Obj = load_obj(fixture("normal-cone.obj")?)?;
+    let raw = obj.to_raw()?;
+    assert!(raw.positions.len() < obj.vertices.len());
+    let mut output = Vec::new();
+    obj.write(&mut output)?;
+    assert_eq!(load_obj::<Vertex, _, u16>(&output[..])?, obj);
+
+    Ok(())
+}
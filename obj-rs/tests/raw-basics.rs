@@ -379,3 +379,160 @@ fn lines_points() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn unrecognized_statements() -> TestResult {
+    use obj::raw::{ParseOptions, StatementPolicy, UnrecognizedStatement, parse_obj_with_options};
+    use obj::{LoadErrorKind, ObjError};
+
+    let input = "v 0 0 0\nlod 5\n# Comment\nzbrush_extension a b\nv 1 1 1\n";
+    let statements = "call a.obj\ncsh ls\nmaplib a.mpc\nusemap a\nusemap off\n";
+    let kind = |result: ObjResult<RawObj>| match result {
+        Err(ObjError::Load(e)) => *e.kind(),
+        _ => panic!("Expected a LoadError"),
+    };
+
+    // Both kinds of statements are errors by default
+    assert_eq!(
+        kind(parse_obj(input.as_bytes())),
+        LoadErrorKind::UnsupportedStatement
+    );
+    let options = ParseOptions {
+        unsupported: StatementPolicy::Skip,
+        ..ParseOptions::default()
+    };
+    assert_eq!(
+        kind(parse_obj_with_options(input.as_bytes(), &options)),
+        LoadErrorKind::UnexpectedStatement
+    );
+
+    let options = ParseOptions {
+        unsupported: StatementPolicy::Skip,
+        unknown: StatementPolicy::Collect,
+    };
+    let obj = parse_obj_with_options(input.as_bytes(), &options)?;
+
+    test! {
+        obj.positions.len(), 2
+        obj.unrecognized_statements, vec![UnrecognizedStatement {
+            line: 4,
+            statement: "zbrush_extension".to_string(),
+            arguments: vec!["a".to_string(), "b".to_string()],
+        }]
+    }
+
+    // Every statement of the format is unsupported rather than unknown
+    let input_with_statements = format!("{}{}", statements, input);
+    assert_eq!(
+        kind(parse_obj(input_with_statements.as_bytes())),
+        LoadErrorKind::UnsupportedStatement
+    );
+    let options = ParseOptions {
+        unsupported: StatementPolicy::Skip,
+        unknown: StatementPolicy::Error,
+    };
+    let obj = parse_obj_with_options(statements.as_bytes(), &options)?;
+    assert!(obj.unrecognized_statements.is_empty());
+    let events = obj::raw::parse_obj_events_with_options(statements.as_bytes(), &options)
+        .collect::<ObjResult<Vec<_>>>()?;
+    assert!(events.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn error_location() {
+    use obj::ObjError;
+
+    let error = |input: &str| match parse_obj(input.as_bytes()) {
+        Err(ObjError::Load(e)) => e,
+        _ => panic!("Expected a LoadError"),
+    };
+
+    let e = error("v 0 0 0\nv 1 1 1\n\nf 1 2 3\n");
+    assert_eq!(e.line(), Some(4));
+    assert_eq!(e.statement(), Some("f"));
+    assert_eq!(e.token(), Some("3"));
+    assert_eq!(
+        e.to_string(),
+        "Received index value out of range: Too big index value (line 4, statement 'f', token '3')"
+    );
+
+    // Lines connected with backslashes are reported as their first line
+    let e = error("v 0 0 0\nv 1 \\\n 1 1 \\\n 1 1\nv 2 2 2\n");
+    assert_eq!(e.line(), Some(2));
+    assert_eq!(e.statement(), Some("v"));
+    assert_eq!(e.token(), None);
+
+    let e = error("v 0 0 0\nvt 1.0 abc\n");
+    assert_eq!(e.line(), Some(2));
+    assert_eq!(e.statement(), Some("vt"));
+    assert_eq!(e.token(), Some("abc"));
+
+    // Numbers which fail to parse are reported as `LoadError`s rather than as
+    // `ObjError::ParseFloat` or `ObjError::ParseInt`
+    assert_eq!(e.kind(), &obj::LoadErrorKind::WrongTypeOfArguments);
+    let e = error("v 0 0 0\nv 1 1 1\nf 1 x 2\n");
+    assert_eq!(e.kind(), &obj::LoadErrorKind::WrongTypeOfArguments);
+    assert_eq!(e.line(), Some(3));
+
+    let e = error("v 0 0 0\nv 1 1 1 \\\n");
+    assert_eq!(e.line(), Some(2));
+    assert_eq!(e.statement(), Some("v"));
+}
+
+#[test]
+fn blocks() -> TestResult {
+    use obj::ObjError;
+    use obj::raw::parse_obj_bytes;
+    use std::io::BufReader;
+
+    // Large enough to be read in several blocks, with lines which are connected with backslashes
+    // across the ends of the blocks
+    let mut input = String::new();
+    for i in 0..20_000 {
+        input += &format!("v {i} 0 0\nf 1 \\\n  -1 \\\n  1\n");
+    }
+
+    // Reads a few bytes at a time
+    let obj = parse_obj(BufReader::with_capacity(7, input.as_bytes()))?;
+    assert_eq!(obj.positions.len(), 20_000);
+    assert_eq!(obj, parse_obj_bytes(input.as_bytes())?);
+
+    // Errors are reported with the line numbers of the whole input
+    input += "v 0 0 0\nf 1 2 \\";
+    match parse_obj(BufReader::with_capacity(7, input.as_bytes())) {
+        Err(ObjError::Load(e)) => assert_eq!(e.line(), Some(80_002)),
+        _ => panic!("Expected a LoadError"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn number_formats() -> TestResult {
+    let input = "v +1 1e5 -2.5E-1\nv 1.0D+03 2d-1 3.0\nvn nan inf -Infinity\nvn 1.#QNAN 1.#IND00 -1.#INF # 1.#INF\nvt +.5 1.#INF0\n";
+    let obj = parse_obj(input.as_bytes())?;
+
+    test! {
+        obj.positions, vec![(1.0, 1e5, -0.25, 1.0), (1000.0, 0.2, 3.0, 1.0)]
+        obj.tex_coords, vec![(0.5, f32::INFINITY, 0.0)]
+    }
+    let [(x, y, z), (u, v, w)] = obj.normals[..] else {
+        panic!("Expected 2 normals, but got {:?}", obj.normals);
+    };
+    assert!(x.is_nan() && u.is_nan() && v.is_nan());
+    assert_eq!(
+        (y, z, w),
+        (f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)
+    );
+
+    for arg in ["x1.#INF", "1.#QNANX", "1.0D", "--1", "1,0"] {
+        assert!(
+            parse_obj(format!("v 0 0 {arg}\n").as_bytes()).is_err(),
+            "{arg}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use obj::raw::{parse_mtl, parse_mtl_file, parse_obj, parse_obj_file};
+use obj::{Obj, ObjError, ObjResult, load_obj, load_obj_file};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+#[test]
+fn obj_file() -> ObjResult<()> {
+    let path = "tests/fixtures/textured-cube.obj";
+    let mut raw = parse_obj_file(path)?;
+
+    assert_eq!(raw.directory.as_deref(), Some(Path::new("tests/fixtures")));
+    assert_eq!(
+        raw.resolve_path(&raw.material_libraries[0]),
+        Path::new("tests/fixtures/cube.mtl")
+    );
+
+    raw.directory = None;
+    assert_eq!(raw, parse_obj(BufReader::new(File::open(path)?))?);
+    assert_eq!(raw.resolve_path("cube.mtl"), Path::new("cube.mtl"));
+
+    let obj: Obj = load_obj_file(path)?;
+    assert_eq!(obj, load_obj(BufReader::new(File::open(path)?))?);
+
+    Ok(())
+}
+
+#[test]
+fn mtl_file() -> ObjResult<()> {
+    let path = "tests/fixtures/cube.mtl";
+    let mut raw = parse_mtl_file(path)?;
+    assert_eq!(raw.directory.as_deref(), Some(Path::new("tests/fixtures")));
+
+    raw.directory = None;
+    assert_eq!(raw, parse_mtl(BufReader::new(File::open(path)?))?);
+
+    Ok(())
+}
+
+#[test]
+fn missing_file() {
+    match parse_obj_file("tests/fixtures/missing.obj") {
+        Err(ObjError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("Expected an IO error, but got {other:?}"),
+    }
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn mapped_file() -> ObjResult<()> {
+    use obj::load_obj_file_mapped;
+    use obj::raw::parse_obj_file_mapped;
+
+    let path = "tests/fixtures/textured-cube.obj";
+    // Safety: The fixtures are never modified while the tests are running
+    let raw = unsafe { parse_obj_file_mapped(path)? };
+    assert_eq!(raw, parse_obj_file(path)?);
+
+    let obj: Obj = unsafe { load_obj_file_mapped(path)? };
+    assert_eq!(obj, load_obj_file(path)?);
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use obj::raw::freeform::{evaluate_curve, evaluate_curve2, evaluate_surface, tessellate};
+use obj::raw::object::{
+    Connection, Curve, Curve2, CurveTechnique, CurveType, FreeFormAttributes, Polygon, Range,
+    SurfaceTechnique, TrimCurve,
+};
+use obj::raw::{RawObj, parse_obj};
+use obj::{LoadOptions, Obj, ObjError, ObjResult, Vertex, load_obj_with_options};
+use std::error::Error;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+macro_rules! assert_approx_eq {
+    ($lhs:expr, $rhs:expr) => {{
+        let (left, right) = ($lhs, $rhs);
+        assert!(
+            left.iter()
+                .zip(right.iter())
+                .all(|(a, b)| (a - b).abs() < 1e-5),
+            "{:?} should be approximately {:?}",
+            left,
+            right
+        );
+    }};
+}
+
+fn fixture(name: &str) -> ObjResult<RawObj> {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    let file = File::open(format!("tests/fixtures/{}", name))?;
+    let input = BufReader::new(file);
+    parse_obj(input)
+}
+
+#[test]
+fn freeform() -> TestResult {
+    let raw = fixture("freeform.obj")?;
+
+    assert_eq!(raw.positions.len(), 16);
+    assert_eq!(raw.param_vertices.len(), 5);
+    assert_eq!(raw.polygons.len(), 0);
+
+    assert_eq!(raw.surfaces.len(), 1);
+    let surface = &raw.surfaces[0];
+    assert_eq!(
+        surface.attributes,
+        FreeFormAttributes {
+            rational: false,
+            curve_type: CurveType::Bezier,
+            degree: (3, 3),
+            basis_matrix: (vec![], vec![]),
+            step: (1, 1),
+        }
+    );
+    assert_eq!(surface.range_u, (0.0, 1.0));
+    assert_eq!(surface.range_v, (0.0, 1.0));
+    assert_eq!(surface.vertices.len(), 16);
+    assert_eq!(surface.vertices[0], (12, None, None));
+    assert_eq!(surface.vertices[15], (3, None, None));
+    assert_eq!(surface.parameters_u, vec![0.0, 1.0]);
+    assert_eq!(surface.parameters_v, vec![0.0, 1.0]);
+    assert_eq!(
+        surface.trims,
+        vec![vec![TrimCurve {
+            start: 0.0,
+            end: 4.0,
+            curve: 0
+        }]]
+    );
+    assert!(surface.holes.is_empty());
+    assert_eq!(
+        surface.technique,
+        Some(SurfaceTechnique::ParametricA {
+            resolution_u: 1.0,
+            resolution_v: 1.0
+        })
+    );
+    assert!(surface.special_curves.is_empty());
+    assert_eq!(surface.special_points, vec![0]);
+
+    assert_eq!(
+        raw.curves2,
+        vec![Curve2 {
+            attributes: FreeFormAttributes {
+                rational: true,
+                curve_type: CurveType::BSpline,
+                degree: (1, 0),
+                basis_matrix: (vec![], vec![]),
+                step: (1, 1),
+            },
+            vertices: vec![0, 1, 2, 3, 4],
+            parameters: vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 4.0],
+            special_points: vec![],
+        }]
+    );
+
+    assert_eq!(
+        raw.curves,
+        vec![Curve {
+            attributes: FreeFormAttributes {
+                rational: false,
+                curve_type: CurveType::BasisMatrix,
+                degree: (3, 0),
+                basis_matrix: (
+                    vec![
+                        1.0, -3.0, 3.0, -1.0, 0.0, 3.0, -6.0, 3.0, 0.0, 0.0, 3.0, -3.0, 0.0, 0.0,
+                        0.0, 1.0
+                    ],
+                    vec![]
+                ),
+                step: (3, 1),
+            },
+            range: (0.0, 1.0),
+            vertices: vec![0, 4, 8, 12],
+            parameters: vec![0.0, 1.0],
+            special_points: vec![],
+            technique: Some(CurveTechnique::Parametric { resolution: 2.0 }),
+        }]
+    );
+
+    let trim = TrimCurve {
+        start: 0.0,
+        end: 4.0,
+        curve: 0,
+    };
+    assert_eq!(
+        raw.connections,
+        vec![Connection {
+            first: (0, trim),
+            second: (0, trim),
+        }]
+    );
+
+    assert_eq!(raw.groups.len(), 2);
+    assert_eq!(
+        raw.groups["patch"].surfaces,
+        vec![Range { start: 0, end: 1 }]
+    );
+    assert_eq!(raw.groups["curve"].curves, vec![Range { start: 0, end: 1 }]);
+
+    Ok(())
+}
+
+#[test]
+fn freeform_errors() {
+    let cases: [&[u8]; 4] = [
+        // Missing `cstype`
+        b"v 0 0 0\nv 1 0 0\ndeg 1\ncurv 0 1 1 2\n",
+        // Missing `deg`
+        b"v 0 0 0\nv 1 0 0\ncstype bezier\ncurv 0 1 1 2\n",
+        // Body statement outside of a body
+        b"parm u 0 1\n",
+        // Reference to an undefined 2D curve
+        b"v 0 0 0\ncstype bezier\ndeg 0 0\nsurf 0 1 0 1 1\ntrim 0 1 1\nend\n",
+    ];
+
+    for case in cases {
+        assert!(parse_obj(case).is_err());
+    }
+}
+
+#[test]
+fn invalid_bases() {
+    let cases: [&[u8]; 6] = [
+        // Knots of a curve which go down
+        b"v 0 0 0\nv 1 0 0\ncstype bspline\ndeg 1\ncurv 0 1 1 2\nparm u 0 1 0 1\nend\n",
+        // Knots of a surface which go down
+        b"v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\ncstype bspline\ndeg 1 1\nsurf 0 1 0 1 1 2 3 4\n\
+          parm u 0 0 1 1\nparm v 0 1 0 1\nend\n",
+        // A knot which is not a number
+        b"v 0 0 0\nv 1 0 0\ncstype bspline\ndeg 1\ncurv 0 1 1 2\nparm u 0 0 NaN 1\nend\n",
+        // A degree which overflows the basis matrix
+        b"v 0 0 0\nv 1 0 0\ncstype bezier\ndeg 100000000000\ncurv 0 1 1 2\nparm u 0 1\nend\n",
+        // A degree which needs more control points than given
+        b"v 0 0 0\nv 1 0 0\ncstype bezier\ndeg 5000\ncurv 0 1 1 2\nparm u 0 1\nend\n",
+        b"v 0 0 0\nv 1 0 0\ncstype taylor\ndeg 1 3\nsurf 0 1 0 1 1 2 1 2\nparm u 0 1\n\
+          parm v 0 1\nend\n",
+    ];
+
+    for case in cases {
+        assert!(parse_obj(case).is_ok());
+        let result: ObjResult<Obj<Vertex>> = load_obj_with_options(case, &LoadOptions::default());
+        assert!(
+            matches!(result, Err(ObjError::Load(_))),
+            "{:?} was accepted",
+            String::from_utf8_lossy(case)
+        );
+    }
+}
+
+#[test]
+fn evaluate() -> TestResult {
+    let raw = fixture("freeform.obj")?;
+
+    let surface = &raw.surfaces[0];
+    assert_approx_eq!(evaluate_surface(&raw, surface, 0.0, 0.0)?, [5.0, -5.0, 0.0]);
+    assert_approx_eq!(evaluate_surface(&raw, surface, 0.5, 0.5)?, [0.0, 0.0, 0.0]);
+    assert_approx_eq!(evaluate_surface(&raw, surface, 1.0, 1.0)?, [-5.0, 5.0, 0.0]);
+
+    let curve = &raw.curves2[0];
+    assert_approx_eq!(evaluate_curve2(&raw, curve, 1.0)?, [0.9, 0.1]);
+    assert_approx_eq!(evaluate_curve2(&raw, curve, 1.5)?, [0.9, 0.5]);
+
+    let curve = &raw.curves[0];
+    assert_approx_eq!(evaluate_curve(&raw, curve, 0.0)?, [-5.0, -5.0, 0.0]);
+    assert_approx_eq!(evaluate_curve(&raw, curve, 0.5)?, [0.0, -5.0, 0.0]);
+
+    Ok(())
+}
+
+#[test]
+fn tessellation() -> TestResult {
+    let mut raw = fixture("freeform.obj")?;
+    tessellate(&mut raw, None, None)?;
+
+    // `ctech cparm 2.0` subdivides the cubic curve into 6 segments
+    assert_eq!(raw.lines.len(), 1);
+    assert_eq!(raw.groups["curve"].lines, vec![Range { start: 0, end: 1 }]);
+
+    // `stech cparma 1.0 1.0` subdivides the bicubic patch into 3x3 quads
+    assert_eq!(raw.polygons.len(), 18);
+    assert_eq!(
+        raw.groups["patch"].polygons,
+        vec![Range { start: 0, end: 18 }]
+    );
+    for polygon in &raw.polygons {
+        match polygon {
+            Polygon::PTN(vertices) => {
+                for &(_, _, n) in vertices {
+                    assert_eq!(raw.normals[n], (0.0, 0.0, 1.0));
+                }
+            }
+            _ => panic!("Expected triangles with texture coordinates and normals"),
+        }
+    }
+
+    let mut raw = fixture("freeform.obj")?;
+    let technique = SurfaceTechnique::ParametricB { resolution: 2.0 };
+    tessellate(&mut raw, None, Some(technique))?;
+    // 6x6 quads, without the triangles outside of the trimming loop
+    assert_eq!(raw.polygons.len(), 50);
+
+    Ok(())
+}
+
+#[test]
+fn trimming() -> TestResult {
+    let input = b"
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vp 0.25 0.25
+vp 0.75 0.25
+vp 0.75 0.75
+vp 0.25 0.75
+vp 0.25 0.25
+cstype bspline
+deg 1
+curv2 1 2 3 4 5
+parm u 0 0 1 2 3 4 4
+end
+cstype bezier
+deg 1 1
+stech cparma 4 4
+surf 0 1 0 1 1 2 3 4
+parm u 0 1
+parm v 0 1
+hole 0 4 1
+end
+";
+    let options = LoadOptions::default();
+    let obj: Obj<Vertex> = load_obj_with_options(&input[..], &options)?;
+
+    // 4x4 quads without the 2x2 quads in the middle
+    assert_eq!(obj.indices.len(), (32 - 8) * 3);
+
+    Ok(())
+}
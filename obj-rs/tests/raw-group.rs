@@ -64,3 +64,66 @@ fn dup_groupnames() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn objects() -> TestResult {
+    let input = BufReader::new(File::open("tests/fixtures/objects.obj")?);
+    let raw = parse_obj(input)?;
+
+    test! {
+        raw.name,                                   None
+        raw.objects.len(),                          2
+        raw.objects["Left  Arm"].polygons.len(),    2
+        raw.objects["Left  Arm"].polygons[0],       Range { start: 1, end: 3 }
+        raw.objects["Left  Arm"].polygons[1],       Range { start: 4, end: 5 }
+        raw.objects["Right Arm"].polygons.len(),    1
+        raw.objects["Right Arm"].polygons[0],       Range { start: 3, end: 4 }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn multiple_groups() -> TestResult {
+    let input = BufReader::new(File::open("tests/fixtures/multi-group.obj")?);
+    let raw = parse_obj(input)?;
+
+    test! {
+        raw.groups.len(),                       4
+        raw.groups["default"].polygons,         vec![Range { start: 0, end: 1 }, Range { start: 4, end: 5 }]
+        raw.groups["body"].polygons,            vec![Range { start: 1, end: 3 }]
+        raw.groups["arm"].polygons,             vec![Range { start: 1, end: 4 }]
+        raw.groups["hand"].polygons,            vec![Range { start: 3, end: 4 }]
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reopened_groups() -> TestResult {
+    let input = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+g a
+f 1 2 3
+g b
+p 1
+g a
+f 1 2 3
+g b
+f 1 2 3
+";
+    let raw = parse_obj(input.as_bytes())?;
+
+    // A reopened group gets a new range, even if its last range has ended right there
+    test! {
+        raw.groups.len(),                       2
+        raw.groups["a"].polygons,               vec![Range { start: 0, end: 1 }, Range { start: 1, end: 2 }]
+        raw.groups["a"].points,                 vec![]
+        raw.groups["b"].points,                 vec![Range { start: 0, end: 1 }]
+        raw.groups["b"].polygons,               vec![Range { start: 2, end: 3 }]
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use obj::ObjResult;
+use obj::raw::{
+    ParseOptions, StatementPolicy, load_materials, load_materials_with, parse_obj, parse_obj_file,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn file_system() -> ObjResult<()> {
+    let obj = parse_obj_file("tests/fixtures/cube.obj")?;
+    let library = load_materials(&obj)?;
+
+    assert_eq!(
+        library.materials.keys().collect::<Vec<_>>(),
+        vec!["Material"]
+    );
+    assert_eq!(
+        library.sources["Material"],
+        Path::new("tests/fixtures/cube.mtl")
+    );
+    assert_eq!(
+        library.resolve_path("Material", "cube-uv-num.png"),
+        Path::new("tests/fixtures/cube-uv-num.png")
+    );
+    assert!(library.missing_materials.is_empty());
+    assert_eq!(
+        library.referenced_files(),
+        vec![PathBuf::from("tests/fixtures/cube-uv-num.png")]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resolver() -> ObjResult<()> {
+    let input = "mtllib a.mtl mats/b.mtl\nv 0 0 0\nusemtl red\np 1\nusemtl green\np 1\nusemtl blue\np 1\nusemtl red\np 1\n";
+    let mut obj = parse_obj(input.as_bytes())?;
+    obj.directory = Some(PathBuf::from("models"));
+
+    let files = HashMap::from([
+        (
+            PathBuf::from("models/a.mtl"),
+            "newmtl red\nKd 1 0 0\nfoo\n".as_bytes().to_vec(),
+        ),
+        (
+            PathBuf::from("models/mats/b.mtl"),
+            "newmtl red\nKd 0.5 0 0\nnewmtl green\nKd spectral green.rfl\nmap_Kd green.png\n"
+                .as_bytes()
+                .to_vec(),
+        ),
+    ]);
+    let options = ParseOptions {
+        unknown: StatementPolicy::Collect,
+        ..ParseOptions::default()
+    };
+    let mut resolver = files;
+    let library = load_materials_with(&obj, &options, &mut resolver)?;
+
+    // The first file which defines a material wins
+    assert_eq!(library.materials.len(), 2);
+    assert_eq!(library.sources["red"], Path::new("models/a.mtl"));
+    assert_eq!(library.sources["green"], Path::new("models/mats/b.mtl"));
+    assert_eq!(
+        library.resolve_path("green", "green.png"),
+        Path::new("models/mats/green.png")
+    );
+    assert_eq!(library.missing_materials, vec!["blue".to_string()]);
+    assert_eq!(
+        library.referenced_files(),
+        vec![
+            PathBuf::from("models/mats/green.png"),
+            PathBuf::from("models/mats/green.rfl"),
+        ]
+    );
+    assert_eq!(library.unrecognized_statements.len(), 1);
+    assert_eq!(
+        library.unrecognized_statements[0].0,
+        Path::new("models/a.mtl")
+    );
+
+    // Referenced files are read with the resolver as well
+    resolver.insert(PathBuf::from("models/mats/green.png"), b"PNG".to_vec());
+    assert_eq!(library.read("green", "green.png", &mut resolver)?, b"PNG");
+    assert!(library.read("red", "green.png", &mut resolver).is_err());
+
+    // Files which cannot be read are errors
+    obj.material_libraries.push("missing.mtl".to_string());
+    assert!(load_materials_with(&obj, &options, &mut resolver).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn unused_materials() -> ObjResult<()> {
+    // `usemtl` statements which no element follows leave no mesh behind
+    let input = "mtllib a.mtl\nv 0 0 0\nusemtl red\np 1\nusemtl blue\nusemtl green\n";
+    let obj = parse_obj(input.as_bytes())?;
+    assert!(!obj.meshes.contains_key("blue"));
+
+    let mut resolver = HashMap::from([(PathBuf::from("a.mtl"), b"newmtl green\n".to_vec())]);
+    let library = load_materials_with(&obj, &ParseOptions::default(), &mut resolver)?;
+    assert_eq!(
+        library.missing_materials,
+        vec!["blue".to_string(), "red".to_string()]
+    );
+
+    Ok(())
+}
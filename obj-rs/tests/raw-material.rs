@@ -155,3 +155,227 @@ fn mtl_map_options() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn unrecognized_statements() -> TestResult {
+    use obj::raw::{ParseOptions, StatementPolicy, parse_mtl_with_options};
+
+    let input = "newmtl Material\nKd 1 1 1\nKm 0.5\nvendor_option 1\n";
+    assert!(parse_mtl(input.as_bytes()).is_err());
+
+    let options = ParseOptions {
+        unsupported: StatementPolicy::Collect,
+        unknown: StatementPolicy::Collect,
+    };
+    let mtl = parse_mtl_with_options(input.as_bytes(), &options)?;
+
+    assert_eq!(mtl.materials.len(), 1);
+    let statements = mtl
+        .unrecognized_statements
+        .iter()
+        .map(|s| (s.line, s.statement.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(statements, [(4, "vendor_option")]);
+
+    Ok(())
+}
+
+#[test]
+fn pbr() -> TestResult {
+    let mtl = fixture("pbr.mtl")?;
+    assert_eq!(mtl.materials.len(), 1);
+
+    let mat = mtl.materials.get("Metal").ok_or("not found")?;
+    let map = |file: &str| {
+        Some(MtlTextureMap {
+            file: file.to_string(),
+            ..MtlTextureMap::default()
+        })
+    };
+    assert_eq!(
+        mat,
+        &Material {
+            diffuse: Some(MtlColor::Rgb(0.8, 0.6, 0.2)),
+            emissive: Some(MtlColor::Rgb(0.0, 0.0, 0.0)),
+            roughness: Some(0.25),
+            metallic: Some(1.0),
+            sheen: Some(0.1),
+            clearcoat_thickness: Some(0.5),
+            clearcoat_roughness: Some(0.03),
+            anisotropy: Some(0.2),
+            anisotropy_rotation: Some(0.75),
+            diffuse_map: map("metal_color.png"),
+            roughness_map: map("metal_roughness.png"),
+            metallic_map: map("metal_metallic.png"),
+            sheen_map: Some(MtlTextureMap {
+                file: "metal_sheen.png".to_string(),
+                channel: MtlTextureChannel::Red,
+                ..MtlTextureMap::default()
+            }),
+            normal_map: Some(MtlTextureMap {
+                file: "metal_normal.png".to_string(),
+                bump_multiplier: 0.5,
+                ..MtlTextureMap::default()
+            }),
+            ..Material::default()
+        }
+    );
+
+    assert!(parse_mtl("newmtl Metal\nPr 0.5 0.5\n".as_bytes()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn to_pbr() -> TestResult {
+    use obj::raw::{AlphaMode, PbrMaterial};
+
+    let mtl = fixture("cube.mtl")?;
+    let pbr = mtl.materials.get("Material").ok_or("not found")?.to_pbr();
+    assert_eq!(pbr.base_color, [0.64, 0.64, 0.64]);
+    assert_eq!(pbr.metallic, 0.0);
+    assert!((pbr.roughness - 0.377_889).abs() < 1e-5);
+    assert_eq!(pbr.opacity, 1.0);
+    assert_eq!(pbr.alpha_mode, AlphaMode::Opaque);
+    assert_eq!(
+        pbr.base_color_map.as_ref().map(|map| &map.file[..]),
+        Some("cube-uv-num.png")
+    );
+
+    let mtl = fixture("pbr.mtl")?;
+    let mat = mtl.materials.get("Metal").ok_or("not found")?;
+    assert_eq!(
+        mat.to_pbr(),
+        PbrMaterial {
+            base_color: [0.8, 0.6, 0.2],
+            base_color_map: mat.diffuse_map.clone(),
+            metallic: 1.0,
+            metallic_map: mat.metallic_map.clone(),
+            roughness: 0.25,
+            roughness_map: mat.roughness_map.clone(),
+            emissive: [0.0; 3],
+            emissive_map: None,
+            opacity: 1.0,
+            opacity_map: None,
+            normal_map: mat.normal_map.clone(),
+            alpha_mode: AlphaMode::Opaque,
+        }
+    );
+
+    let mtl =
+        parse_mtl("newmtl a\nillum 3\nTr 0.25\nbump n.png\nnewmtl b\nmap_d leaf.png\n".as_bytes())?;
+    let a = mtl.materials.get("a").ok_or("not found")?.to_pbr();
+    assert_eq!(a.base_color, [1.0; 3]);
+    assert_eq!(a.metallic, 1.0);
+    assert_eq!(a.roughness, 1.0);
+    assert_eq!(a.opacity, 0.75);
+    assert_eq!(a.alpha_mode, AlphaMode::Blend);
+    assert_eq!(
+        a.normal_map.as_ref().map(|map| &map.file[..]),
+        Some("n.png")
+    );
+    let b = mtl.materials.get("b").ok_or("not found")?.to_pbr();
+    assert_eq!(b.alpha_mode, AlphaMode::Mask);
+
+    Ok(())
+}
+
+#[test]
+fn reflection() -> TestResult {
+    use obj::raw::material::MtlReflectionType;
+
+    let mtl = fixture("reflection.mtl")?;
+    let mat = mtl.materials.get("Chrome").ok_or("not found")?;
+    let reflection = |file: &str, reflection_type| MtlTextureMap {
+        file: file.to_string(),
+        reflection_type: Some(reflection_type),
+        ..MtlTextureMap::default()
+    };
+    assert_eq!(
+        mat,
+        &Material {
+            diffuse: Some(MtlColor::Rgb(0.1, 0.1, 0.1)),
+            mirror_color: Some(MtlColor::Rgb(0.5, 0.5, 0.5)),
+            illumination_model: Some(3),
+            sharpness: Some(200.0),
+            antialias_textures: Some(true),
+            displacement_map: Some(MtlTextureMap {
+                file: "chrome_height.png".to_string(),
+                base_gain: [0.0, 0.1],
+                ..MtlTextureMap::default()
+            }),
+            decal_map: Some(MtlTextureMap {
+                file: "chrome_decal.png".to_string(),
+                ..MtlTextureMap::default()
+            }),
+            reflection_maps: vec![
+                reflection("chrome_top.png", MtlReflectionType::CubeTop),
+                reflection("chrome_bottom.png", MtlReflectionType::CubeBottom),
+                reflection("chrome_sphere.png", MtlReflectionType::Sphere),
+            ],
+            ..Material::default()
+        }
+    );
+
+    assert!(parse_mtl("newmtl a\nmap_aat yes\n".as_bytes()).is_err());
+    assert!(parse_mtl("newmtl a\nrefl -type cylinder a.png\n".as_bytes()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn texture_options() -> TestResult {
+    let mtl = fixture("texture_options.mtl")?;
+    let mat = mtl.materials.get("Options").ok_or("not found")?;
+
+    assert_eq!(
+        mat.diffuse_map,
+        Some(MtlTextureMap {
+            file: "My  Texture.png".to_string(),
+            color_correction: true,
+            boost: 2.5,
+            origin_offset: [0.5, 0.0, 0.0],
+            scale: [2.0, 3.0, 1.0],
+            ..MtlTextureMap::default()
+        })
+    );
+    assert_eq!(
+        mat.specular_map,
+        Some(MtlTextureMap {
+            file: "specular map.png".to_string(),
+            clamping: true,
+            unknown_options: vec![
+                (
+                    "-vendor".to_string(),
+                    vec!["1".to_string(), "2".to_string()]
+                ),
+                ("-flag".to_string(), vec![]),
+            ],
+            ..MtlTextureMap::default()
+        })
+    );
+    assert_eq!(
+        mat.bump_map,
+        Some(MtlTextureMap {
+            file: "bump.png".to_string(),
+            bump_multiplier: 0.3,
+            channel: MtlTextureChannel::Luminance,
+            ..MtlTextureMap::default()
+        })
+    );
+
+    for input in [
+        "newmtl a\nmap_Kd -imfchan x a.png\n",
+        "newmtl a\nmap_Kd -clamp maybe a.png\n",
+        "newmtl a\nmap_Kd -s a.png\n",
+        "newmtl a\nmap_Kd -bm 2\n",
+    ] {
+        assert!(
+            parse_mtl(input.as_bytes()).is_err(),
+            "{:?} was accepted",
+            input
+        );
+    }
+
+    Ok(())
+}
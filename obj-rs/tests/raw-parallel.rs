@@ -0,0 +1,25 @@
+#![cfg(feature = "rayon")]
+
+use obj::raw::{
+    ParseOptions, StatementPolicy, parse_obj_bytes_with_options, parse_obj_parallel_with_options,
+};
+use std::fs;
+
+#[test]
+fn same_as_parse_obj() {
+    let options = ParseOptions {
+        unsupported: StatementPolicy::Collect,
+        unknown: StatementPolicy::Collect,
+    };
+
+    // Large enough to be split into several parts
+    for name in ["scene", "textured-cube"] {
+        let mut input = fs::read(format!("tests/fixtures/{name}.obj")).unwrap();
+        input.push(b'\n');
+        let input = input.repeat((3 << 19) / input.len() + 1);
+
+        let expected = parse_obj_bytes_with_options(&input, &options).unwrap();
+        let parallel = parse_obj_parallel_with_options(&input, &options).unwrap();
+        assert!(parallel == expected, "{name}");
+    }
+}
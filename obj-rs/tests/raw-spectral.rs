@@ -0,0 +1,110 @@
+use obj::ObjResult;
+use obj::raw::material::MtlColor;
+use obj::raw::{SpectralCurve, load_materials_with, parse_obj, parse_rfl, parse_rfl_bytes};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+
+fn assert_rgb(actual: [f32; 3], expected: [f32; 3]) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 2e-3, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn parse() -> ObjResult<()> {
+    let curve = parse_rfl(BufReader::new(File::open("tests/fixtures/sand.rfl")?))?;
+    assert_eq!(
+        curve,
+        SpectralCurve {
+            min: 400.0,
+            max: 700.0,
+            values: vec![0.2, 0.25, 0.3, 0.35, 0.4, 0.45, 0.5],
+        }
+    );
+    assert_eq!(curve.reflectance(300.0), 0.2);
+    assert_eq!(curve.reflectance(425.0), 0.225);
+    assert_eq!(curve.reflectance(800.0), 0.5);
+
+    // Curves built by hand may be degenerate
+    let curve = |min, max, values: &[f32]| SpectralCurve {
+        min,
+        max,
+        values: values.to_vec(),
+    };
+    assert_eq!(curve(400.0, 700.0, &[]).reflectance(500.0), 0.0);
+    assert_eq!(curve(400.0, 700.0, &[0.3]).reflectance(500.0), 0.3);
+    assert_eq!(curve(500.0, 500.0, &[0.3, 0.6]).reflectance(500.0), 0.3);
+    assert_eq!(curve(700.0, 400.0, &[0.3, 0.6]).reflectance(500.0), 0.3);
+    assert_rgb(curve(400.0, 700.0, &[]).to_rgb(), [0.0; 3]);
+
+    for input in [
+        "min 400\n0.5 0.5\n",
+        "min 400\nmax 700\n0.5\n",
+        "min 400\nmax 700\nfoo\n",
+    ] {
+        assert!(
+            parse_rfl_bytes(input.as_bytes()).is_err(),
+            "{:?} was accepted",
+            input
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn to_rgb() -> ObjResult<()> {
+    let white = parse_rfl_bytes(b"min 380\nmax 780\n1 1\n")?;
+    assert_rgb(white.to_rgb(), [1.0; 3]);
+
+    let spectral = MtlColor::Spectral("white.rfl".to_string(), 0.5);
+    assert_eq!(spectral.to_rgb(None), None);
+    assert_rgb(spectral.to_rgb(Some(&white)).unwrap(), [0.5; 3]);
+
+    let [r, g, b] = parse_rfl_bytes(&fs::read("tests/fixtures/sand.rfl")?)?.to_rgb();
+    assert!(r > g && g > b, "sand is not reddish: {:?}", [r, g, b]);
+
+    // The D65 white point
+    assert_rgb(
+        MtlColor::Xyz(0.95047, 1.0, 1.08883).to_rgb(None).unwrap(),
+        [1.0; 3],
+    );
+    assert_eq!(
+        MtlColor::Rgb(0.1, 0.2, 0.3).to_rgb(None),
+        Some([0.1, 0.2, 0.3])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn library() -> ObjResult<()> {
+    let obj = parse_obj("mtllib assets/sand.mtl\nusemtl sand\n".as_bytes())?;
+    let mut files = HashMap::from([
+        (
+            PathBuf::from("assets/sand.mtl"),
+            b"newmtl sand\nKd spectral sand.rfl 2\nKs xyz 0.5\n".to_vec(),
+        ),
+        (
+            PathBuf::from("assets/sand.rfl"),
+            fs::read("tests/fixtures/sand.rfl")?,
+        ),
+    ]);
+    let library = load_materials_with(&obj, &Default::default(), &mut files)?;
+    let sand = &library.materials["sand"];
+
+    let curve = parse_rfl_bytes(&files[&PathBuf::from("assets/sand.rfl")])?;
+    let expected = curve.to_rgb().map(|value| value * 2.0);
+    let diffuse = sand.diffuse.as_ref().unwrap();
+    assert_eq!(library.to_rgb("sand", diffuse, &mut files)?, expected);
+
+    let specular = sand.specular.as_ref().unwrap();
+    assert_eq!(
+        library.to_rgb("sand", specular, &mut files)?,
+        specular.to_rgb(None).unwrap()
+    );
+
+    Ok(())
+}
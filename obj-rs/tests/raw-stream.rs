@@ -0,0 +1,179 @@
+use obj::raw::object::{Line, Polygon};
+use obj::raw::{
+    ObjEvent, ParseOptions, StatementPolicy, parse_obj, parse_obj_events,
+    parse_obj_events_with_options,
+};
+use obj::{ObjError, ObjResult};
+use std::fs::File;
+use std::io::BufReader;
+
+fn events(input: &str) -> ObjResult<Vec<ObjEvent>> {
+    parse_obj_events(input.as_bytes()).collect()
+}
+
+#[test]
+fn stream() -> ObjResult<()> {
+    let events = events(
+        "mtllib a.mtl b.mtl
+o  Left  Arm
+g arm left
+usemtl skin
+s 1
+v 0 0 0
+v 1 0 0
+v 1 1 0
+vt 0 0
+vn 0 0 1
+f 1/1/1 2/1/1 -1/-1/-1
+s off
+mg 2
+l 1 2
+p -1
+g
+o
+",
+    )?;
+
+    assert_eq!(
+        events,
+        vec![
+            ObjEvent::MtlLib(vec!["a.mtl".to_string(), "b.mtl".to_string()]),
+            ObjEvent::Object(Some("Left  Arm".to_string())),
+            ObjEvent::Group(vec!["arm".to_string(), "left".to_string()]),
+            ObjEvent::UseMtl("skin".to_string()),
+            ObjEvent::Smoothing(Some(1)),
+            ObjEvent::Position(0.0, 0.0, 0.0, 1.0),
+            ObjEvent::Position(1.0, 0.0, 0.0, 1.0),
+            ObjEvent::Position(1.0, 1.0, 0.0, 1.0),
+            ObjEvent::TexCoord(0.0, 0.0, 0.0),
+            ObjEvent::Normal(0.0, 0.0, 1.0),
+            ObjEvent::Face(Polygon::PTN(vec![(0, 0, 0), (1, 0, 0), (2, 0, 0)])),
+            ObjEvent::Smoothing(None),
+            ObjEvent::Merging(Some(2)),
+            ObjEvent::Line(Line::P(vec![0, 1])),
+            ObjEvent::Points(vec![2]),
+            ObjEvent::Group(vec!["default".to_string()]),
+            ObjEvent::Object(None),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn same_as_parse_obj() -> ObjResult<()> {
+    for name in [
+        "cube",
+        "dome",
+        "group",
+        "lines_points",
+        "textured-cube",
+        "untitled",
+    ] {
+        let path = format!("tests/fixtures/{name}.obj");
+        let raw = parse_obj(BufReader::new(File::open(&path)?))?;
+
+        let (mut positions, mut tex_coords, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+        let (mut points, mut lines, mut polygons) = (Vec::new(), Vec::new(), Vec::new());
+        for event in parse_obj_events(BufReader::new(File::open(&path)?)) {
+            match event? {
+                ObjEvent::Position(x, y, z, w) => positions.push((x, y, z, w)),
+                ObjEvent::TexCoord(u, v, w) => tex_coords.push((u, v, w)),
+                ObjEvent::Normal(x, y, z) => normals.push((x, y, z)),
+                ObjEvent::Points(mut vec) => points.append(&mut vec),
+                ObjEvent::Line(line) => lines.push(line),
+                ObjEvent::Face(polygon) => polygons.push(polygon),
+                _ => {}
+            }
+        }
+
+        assert_eq!(positions, raw.positions, "{name}");
+        assert_eq!(tex_coords, raw.tex_coords, "{name}");
+        assert_eq!(normals, raw.normals, "{name}");
+        assert_eq!(points, raw.points, "{name}");
+        assert_eq!(lines, raw.lines, "{name}");
+        assert_eq!(polygons, raw.polygons, "{name}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn unrecognized() -> ObjResult<()> {
+    let input = "v 0 0 0\nfoo bar\nv 1 1 1\nf 1 2 -1\n";
+
+    match events(input) {
+        Err(ObjError::Load(e)) => assert_eq!(e.line(), Some(2)),
+        other => panic!("expected an error, got {other:?}"),
+    }
+    let mut stream = parse_obj_events(input.as_bytes());
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_err());
+
+    let options = ParseOptions {
+        unknown: StatementPolicy::Collect,
+        ..ParseOptions::default()
+    };
+    let events =
+        parse_obj_events_with_options(input.as_bytes(), &options).collect::<ObjResult<Vec<_>>>()?;
+    assert_eq!(events.len(), 4);
+    match &events[1] {
+        ObjEvent::Unrecognized(stmt) => {
+            assert_eq!((stmt.line, stmt.statement.as_str()), (2, "foo"));
+            assert_eq!(stmt.arguments, vec!["bar".to_string()]);
+        }
+        other => panic!("expected an unrecognized statement, got {other:?}"),
+    }
+
+    let options = ParseOptions {
+        unknown: StatementPolicy::Skip,
+        ..ParseOptions::default()
+    };
+    let events =
+        parse_obj_events_with_options(input.as_bytes(), &options).collect::<ObjResult<Vec<_>>>()?;
+    assert_eq!(events.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn blocks() -> ObjResult<()> {
+    // Large enough to be lexed in several blocks, with lines which are connected with
+    // backslashes across the ends of the blocks
+    let mut input = String::new();
+    for i in 0..20_000 {
+        input += &format!("v {i} 0 0\nf 1 \\\n  -1 \\\n  1\n");
+    }
+    input += "foo\nv 0 0 0\nf 1 2 \\";
+
+    // Reads a few bytes at a time
+    let reader = BufReader::with_capacity(7, input.as_bytes());
+    let events = parse_obj_events(reader).collect::<Vec<_>>();
+    assert_eq!(events.len(), 40_003);
+
+    let mut faces = 0;
+    for (i, event) in events[..40_000].iter().enumerate() {
+        match event {
+            Ok(ObjEvent::Position(x, ..)) => assert_eq!(*x as usize, i / 2),
+            Ok(ObjEvent::Face(Polygon::P(vertices))) => {
+                assert_eq!(vertices, &[0, i / 2, 0]);
+                faces += 1;
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+    assert_eq!(faces, 20_000);
+
+    // The error of a statement does not end the iteration, but an incomplete line does
+    match &events[40_000] {
+        Err(ObjError::Load(e)) => assert_eq!(e.line(), Some(80_001)),
+        other => panic!("expected an error, got {other:?}"),
+    }
+    assert!(matches!(events[40_001], Ok(ObjEvent::Position(..))));
+    match &events[40_002] {
+        Err(ObjError::Load(e)) => assert_eq!(e.line(), Some(80_003)),
+        other => panic!("expected an error, got {other:?}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use obj::ObjResult;
+use obj::raw::object::{Group, Range, RawObj};
+use obj::raw::{parse_mtl, parse_obj, write_mtl, write_obj};
+use std::fs::File;
+use std::io::BufReader;
+
+/// Merges adjacent ranges of every group, which `write_obj` does not preserve.
+fn canonical(mut raw: RawObj) -> RawObj {
+    fn merge(ranges: &mut Vec<Range>) {
+        let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => merged.push(range),
+            }
+        }
+        *ranges = merged;
+    }
+    let groups = raw
+        .objects
+        .values_mut()
+        .chain(raw.groups.values_mut())
+        .chain(raw.meshes.values_mut())
+        .chain(raw.smoothing_groups.values_mut())
+        .chain(raw.merging_groups.values_mut());
+    for group in groups {
+        let Group {
+            points,
+            lines,
+            polygons,
+            curves,
+            surfaces,
+        } = group;
+        for ranges in [points, lines, polygons, curves, surfaces] {
+            merge(ranges);
+        }
+    }
+    raw
+}
+
+#[test]
+fn round_trip() -> ObjResult<()> {
+    let fixtures = [
+        "cube.obj",
+        "dome.obj",
+        "empty.obj",
+        "freeform.obj",
+        "group.obj",
+        "lines_points.obj",
+        "multi-group.obj",
+        "normal-cone.obj",
+        "objects.obj",
+        "scene.obj",
+        "textured-cube.obj",
+        "untitled.obj",
+    ];
+
+    for fixture in fixtures {
+        let input = BufReader::new(File::open(format!("tests/fixtures/{}", fixture))?);
+        let raw = parse_obj(input)?;
+
+        let mut output = Vec::new();
+        write_obj(&raw, &mut output)?;
+        assert_eq!(
+            canonical(parse_obj(&output[..])?),
+            canonical(raw),
+            "{} was not round-tripped",
+            fixture
+        );
+    }
+
+    // Elements are written by kind, so a group which is reopened in between results in a single
+    // range
+    let raw = parse_obj("v 0 0 0\ng a\nf 1 1 1\ng b\np 1\ng a\nf 1 1 1\n".as_bytes())?;
+    assert_eq!(raw.groups["a"].polygons.len(), 2);
+    let mut output = Vec::new();
+    write_obj(&raw, &mut output)?;
+    let written = parse_obj(&output[..])?;
+    assert_eq!(written.groups["a"].polygons.len(), 1);
+    assert_eq!(canonical(written), canonical(raw));
+
+    // Materials which no element follows
+    let raw = parse_obj("v 0 0 0\nusemtl a\np 1\nusemtl b\n".as_bytes())?;
+    let mut output = Vec::new();
+    write_obj(&raw, &mut output)?;
+    assert_eq!(parse_obj(&output[..])?, raw);
+
+    Ok(())
+}
+
+#[test]
+fn write() -> ObjResult<()> {
+    let input = "
+v 0 0 0 2
+v 1 0 0
+v 0 1 0
+vt 0.5
+vt 0.25 0.75
+o Two  Spaces
+g a b
+usemtl red
+f 1/1 2/2 3/2
+s 1
+l 1 2
+";
+    let mut output = Vec::new();
+    write_obj(&parse_obj(input.as_bytes())?, &mut output)?;
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+v 0 0 0 2
+v 1 0 0
+v 0 1 0
+vt 0.5
+vt 0.25 0.75
+o Two  Spaces
+g a b
+usemtl red
+s 1
+l 1 2
+s off
+f 1/1 2/2 3/2
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mtl_round_trip() -> ObjResult<()> {
+    for fixture in [
+        "cube.mtl",
+        "map_options.mtl",
+        "pbr.mtl",
+        "reflection.mtl",
+        "untitled.mtl",
+    ] {
+        let input = BufReader::new(File::open(format!("tests/fixtures/{}", fixture))?);
+        let raw = parse_mtl(input)?;
+
+        let mut output = Vec::new();
+        write_mtl(&raw, &mut output)?;
+        assert_eq!(
+            parse_mtl(&output[..])?,
+            raw,
+            "{} was not round-tripped",
+            fixture
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mtl_write() -> ObjResult<()> {
+    let input = "
+newmtl b
+Kd spectral sand.rfl 0.5
+Ks xyz 0.25
+map_Kd -s 2 2 1 -clamp on -imfchan l wood.png
+
+newmtl a
+Tr 0.25
+bump -bm 0.5 bump.png
+";
+    let mut output = Vec::new();
+    write_mtl(&parse_mtl(input.as_bytes())?, &mut output)?;
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+newmtl a
+d 0.75
+bump -bm 0.5 bump.png
+
+newmtl b
+Kd spectral sand.rfl 0.5
+Ks xyz 0.25 0.25 0.25
+map_Kd -s 2 2 1 -clamp on -imfchan l wood.png
+"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use obj::{Model, ObjResult, Position, Scene, load_scene};
+use std::fs::File;
+use std::io::BufReader;
+
+#[test]
+fn scene() -> ObjResult<()> {
+    let input = BufReader::new(File::open("tests/fixtures/scene.obj")?);
+    let scene: Scene<Position> = load_scene(input)?;
+
+    assert_eq!(scene.vertices.len(), 6);
+    assert_eq!(scene.indices.len(), 18);
+
+    let model = |group: &str, material: &str, indices| Model {
+        object: Some("Scene".to_string()),
+        groups: vec![group.to_string()],
+        material: Some(material.to_string()),
+        indices,
+    };
+    assert_eq!(
+        scene.models,
+        vec![
+            model("floor", "stone", 0..6),
+            model("floor", "wood", 6..12),
+            model("wall", "stone", 12..15),
+            model("wall", "wood", 15..18),
+        ]
+    );
+
+    // Polygons of the same model are gathered even if they are apart in the file
+    assert_eq!(scene.indices[6..12], [0, 1, 5, 2, 3, 5]);
+
+    Ok(())
+}
+
+#[test]
+fn objects() -> ObjResult<()> {
+    let input = BufReader::new(File::open("tests/fixtures/objects.obj")?);
+    let scene: Scene<Position> = load_scene(input)?;
+
+    let model = |object: Option<&str>, indices| Model {
+        object: object.map(str::to_string),
+        groups: vec!["default".to_string()],
+        material: None,
+        indices,
+    };
+    assert_eq!(
+        scene.models,
+        vec![
+            model(None, 0..6),
+            model(Some("Left  Arm"), 6..15),
+            model(Some("Right Arm"), 15..18),
+        ]
+    );
+
+    Ok(())
+}